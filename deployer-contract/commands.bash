@@ -20,36 +20,216 @@ soroban contract build
 ## Deploy the contract to the futurenet.
 soroban contract deploy --wasm /Users/alberto/Documents/Cash-abroad/soroban-contracts/target/wasm32-unknown-unknown/release/deployer_contract.wasm --source cashabroad --network testnet
 
-## Invoke the init function.
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- init --admin cashabroad --token_address CB64D3G7SM2RTH6JSGG34DDTFTQ5CFDKVDZJZSODMCX4NJ2HV2KN7OHT --deadline 1701028905 --associations '[ "GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK", "GBRADTPI7RK666QPUWP5TPVE27TCZERRHBUF6OMX3LV4ZGZN7Z5USNEM" ]'
+## Deploy the manager contract, which keeps the admin, the roles, the applications and the challenges of the voting contract,
+## and the query contract, which reads the rounds of the voting contract.
+MANAGER=$(soroban contract deploy --wasm /Users/alberto/Documents/Cash-abroad/soroban-contracts/target/wasm32-unknown-unknown/release/manager_contract.wasm --source cashabroad --network testnet)
+QUERY=$(soroban contract deploy --wasm /Users/alberto/Documents/Cash-abroad/soroban-contracts/target/wasm32-unknown-unknown/release/query_contract.wasm --source cashabroad --network testnet)
 
-## Calculate funding 
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- calculate_funding --admin cashabroad
+## Upload the wasm of the transfer-contract, it returns the hash used by set_distribution_wasm.
+soroban contract install --wasm /Users/alberto/Documents/Cash-abroad/soroban-contracts/target/wasm32-unknown-unknown/release/transfer_contract.wasm --source cashabroad --network testnet
 
-##Invoke the withdraw function
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- withdraw --admin cashabroad
+## Invoke the init function of the manager contract, it initializes the voting contract, then the one of the query contract.
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- init --admin cashabroad --voting CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- init --voting CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ
 
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- end_funding --admin cashabroad
+## Create a funding round, it returns the round id used by the rest of the functions.
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- create_round --admin cashabroad --token_address CB64D3G7SM2RTH6JSGG34DDTFTQ5CFDKVDZJZSODMCX4NJ2HV2KN7OHT --deadline 1701028905 --associations '[ "GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK", "GBRADTPI7RK666QPUWP5TPVE27TCZERRHBUF6OMX3LV4ZGZN7Z5USNEM" ]' --config '{ "precision": 7, "mode": 0, "pairwise_threshold": "0", "cap": "0", "cap_bps": 0 }'
+
+## Open the donations of a round (after the setup or the applications)
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- open_donations --admin cashabroad --round_id 0
+
+## In the pairwise mode, tally the pairs of donors 50 at a time until it returns 0, before calculate_funding
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- tally_pairs --round_id 0 --limit 50
+
+## Calculate funding, it closes the donations
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- calculate_funding --admin cashabroad --round_id 0
+
+## Put the calculated funding in review
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- end_funding --admin cashabroad --round_id 0
+
+## Approve the reviewed funding
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- start_payout --admin cashabroad --round_id 0
+
+##Invoke the withdraw function, the admin of the voting contract then accepts the admin of the distribution contract (see distribution_address)
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- withdraw --admin cashabroad --round_id 0
+soroban contract invoke --id ___ --source cashabroad --network testnet -- accept_admin --new_admin cashabroad
+
+## Return the matching the associations did not get to the sponsors after the withdraw, 50 at a time until it returns 0
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- return_unmatched --round_id 0 --limit 50
 
 ## invoke the add_association function
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- add_association --admin cashabroad --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- add_association --admin cashabroad --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
 
 ## Add funds to an asociation
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sender --network testnet -- deposit --sender sender --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA --amount 1000
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sender --network testnet -- deposit --sender sender --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA --amount 1000
 
-## get the state of the contract: 0 means running and 1 means ended
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- state  
+## get the state of a round: 0 setup, 1 applications, 2 donations, 3 calculated, 4 review, 5 payout, 6 closed, 7 cancelled and 8 ended
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- state --round_id 0  
 
 ## Get the associations
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- associations_addresses  
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- associations_addresses --round_id 0  
 
 ## Get the association balance
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- association_amount --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- association_amount --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
 
 ## Get the association balances
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- associations_amounts
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- associations_amounts --round_id 0
  
  ## Get the total amount
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- total_amount  
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- total_amount --round_id 0  
+
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- total_final_associations --round_id 0
+
+## Add funds to the matching pool
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sponsor --network testnet -- fund_matching_pool --sponsor sponsor --round_id 0 --amount 1000
+
+## Get the matching pool
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- matching_pool --round_id 0
+
+
+## Get the contribution of each donor to an association
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- association_donors --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+
+## Cancel a round, the donors and sponsors can then get their deposits back with refund
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- cancel_round --admin cashabroad --round_id 0
+
+## Refund the deposits of a cancelled round
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sender --network testnet -- refund --donor sender --round_id 0
+
+## Remove an association from a round, then refund its donors 50 at a time until it returns 0 (also for a suspended association before the withdraw)
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- remove_association --admin cashabroad --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- refund_association --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA --limit 50
+
+## Suspend an association of a round (use --suspended false to resume it)
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- suspend_association --admin cashabroad --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA --suspended true
+
+## Grant a role to an address: 0 owner, 1 operator, 2 treasurer, 3 reviewer (here the reviewer of the applications)
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- grant_role --owner cashabroad --role 3 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Revoke a role
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- revoke_role --owner cashabroad --role 3 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Get the addresses granted a role and check if an address has it
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- role_members --role 3
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- has_role --role 3 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Open the applications of a round until the application deadline
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- open_applications --admin cashabroad --round_id 0 --application_deadline 1701302400
+
+## Apply to a round as an association
+soroban contract invoke --id $MANAGER --source association --network testnet -- apply --association association --round_id 0 --metadata "We build schools"
+
+## Approve or reject an application
+soroban contract invoke --id $MANAGER --source reviewer --network testnet -- approve_application --reviewer reviewer --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+soroban contract invoke --id $MANAGER --source reviewer --network testnet -- reject_application --reviewer reviewer --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA
+
+## Get the applications of a round: 0 means pending, 1 means approved and 2 means rejected
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- applications --round_id 0 --status 0 --offset 0 --limit 50
+
+## Set the challenge period of a round in seconds, the funding stays in review that long after end_funding
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- set_challenge_period --admin cashabroad --round_id 0 --challenge_period 86400
+
+## Challenge the funding of a round as an association or a donor
+soroban contract invoke --id $MANAGER --source sender --network testnet -- challenge --challenger sender --round_id 0 --reason "The matching is wrong"
+
+## Resolve a challenge, it keeps blocking start_payout until then, an upheld challenge sends the round back to Ended to calculate the funding again
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- resolve_challenge --admin cashabroad --round_id 0 --challenger GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN --upheld false
+
+## Get the challenges and the end of the challenge period of a round
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- challenges --round_id 0 --offset 0 --limit 50
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- review_deadline --round_id 0
+
+## Set the hash of the uploaded transfer-contract wasm, withdraw deploys a distribution contract from it for every round
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- set_distribution_wasm --admin cashabroad --wasm_hash ___
+
+## Get the distribution contract deployed for a round
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- distribution_address --round_id 0
+
+## Set how many ledgers the storage lives, every call bumps the entries back up to high once they drop below low
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- set_ttl_config --admin cashabroad --config '{ "low": 518400, "high": 1555200 }'
+
+## Bump the storage of a round, anyone can call it to keep an idle round alive, one page of 50 associations at a time, then 50 donors of each association at a time,
+## the manager contract bumps the applications and challenges of a round the same way and the query contract only its own instance
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- extend_ttl --round_id 0 --offset 0 --limit 50
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- extend_ttl --round_id 0 --offset 0 --limit 50
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- extend_ttl
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- extend_association_ttl --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --offset 0 --limit 50
+
+## Get the ttl config
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- ttl_config
+
+## Get the associations of a round page by page (at most 50 per page), count returns how many there are
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- count --round_id 0
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- associations_amounts_page --round_id 0 --offset 0 --limit 50
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- total_final_associations_page --round_id 0 --offset 0 --limit 50
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- association_donors_page --round_id 0 --association GB4XTGTAZFH57VXWQETJ5RDVLUCQ7QN4SKELZTMWBU6TB6YQZFVQWFLA --offset 0 --limit 50
+
+## Accept other tokens than the base token of a round, valued with the price oracle (see mock-oracle-contract to try it out)
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- set_token_config --admin cashabroad --round_id 0 --config '{ "oracle": "___", "tokens": [ "___" ], "max_price_age": 3600 }'
+
+## Deposit in one of the accepted tokens, the association gets paid in that token on withdraw
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- deposit_token --sender sender --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --token ___ --amount 200000000
+
+## Get the accepted tokens of a round and what an association received in each of them
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- token_config --round_id 0
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- association_tokens --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK
+
+## Deposit with a memo, public makes it show in the memos query and the deposit event
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sender --network testnet -- deposit --sender sender --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --amount 1000 --memo '{ "text": "REF 2023-0042", "public": true }'
+
+## Get the public memos of the deposits to an association page by page (at most 50 per page), memo_count returns how many memos there are
+soroban contract invoke --id $QUERY --source sender --network testnet -- memos --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --offset 0 --limit 50
+soroban contract invoke --id $QUERY --source sender --network testnet -- memo_count --round_id 0 --association GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK
+
+## Turn on the commit-reveal mode of a round: the donors commit until the deadline and reveal until reveal_deadline,
+## the commitments not revealed go to the matching pool when forfeit is true, back to their donors otherwise
+soroban contract invoke --id $MANAGER --network testnet --source cashabroad -- set_commit_reveal --admin cashabroad --round_id 0 --config '{ "reveal_deadline": 1694735999, "forfeit": false }'
+
+## Commit to a round (again to add to the amount), the hash is the sha256 of the XDR of the ScVal vector [contract, round_id, donor, allocations, salt]
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --network testnet --source sender -- commit --donor sender --round_id 0 --hash ___ --amount 1000
+
+## Reveal the allocations after the deadline, they have to add up to the committed amount
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --network testnet --source sender -- reveal --donor sender --round_id 0 --allocations '{ "GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK": "600", "GBRADTPI7RK666QPUWP5TPVE27TCZERRHBUF6OMX3LV4ZGZN7Z5USNEM": "400" }' --salt ___
+
+## Settle the commitments not revealed after the reveal deadline, 50 at a time until it returns 0, before calculate_funding
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --network testnet --source cashabroad -- settle_commitments --round_id 0 --limit 50
+
+## Get the commit-reveal config of a round and the pending commitment of a donor
+soroban contract invoke --id $QUERY --network testnet --source cashabroad -- commit_reveal --round_id 0
+soroban contract invoke --id $QUERY --network testnet --source cashabroad -- commitment --round_id 0 --donor GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Pledge to match the direct donations of a round 1:1 (10000 bps) up to a budget, the unused budget is returned on withdraw
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --network testnet --source sender -- pledge --sponsor sender --round_id 0 --multiplier_bps 10000 --budget 5000
+
+## Get the pledges of a round and what they matched
+soroban contract invoke --id $QUERY --network testnet --source cashabroad -- pledges --round_id 0
+
+## Get the number of applications, sponsors, pledges and challenges of a round, their queries are paged
+soroban contract invoke --id $QUERY --network testnet --source cashabroad -- round_counts --round_id 0
+
+## Set the contribution limits of a round in the base token, 0 for no limit
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- set_contribution_limits --admin cashabroad --round_id 0 --limits '{ "min_deposit": "100", "max_per_donor": "100000", "max_per_association": "20000" }'
+
+## Get the contribution limits of a round
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- contribution_limits --round_id 0
+
+## Set the ed25519 public key (hex) of the verifier of a round, then only the attested donors count for the matching
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- set_verifier --admin cashabroad --round_id 0 --verifier ___
+
+## Present the attestation of a donor, the signature of the verifier over the XDR of the ScVal vector [contract, donor, round_id, expiry]
+soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source sender --network testnet -- attest --donor GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN --round_id 0 --expiry 1694735999 --signature ___
+
+## Get the verifier of a round and check if a donor is attested
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- verifier --round_id 0
+soroban contract invoke --id $QUERY --source cashabroad --network testnet -- is_attested --round_id 0 --donor GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Hand over the admin in two steps: the admin proposes the new one, which accepts with its own key
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- propose_admin --admin cashabroad --new_admin GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+soroban contract invoke --id $MANAGER --source newadmin --network testnet -- accept_admin --new_admin GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Or cancel the proposal before it is accepted
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- cancel_admin --admin cashabroad
 
-soroban contract invoke --id CDQIB6DDVBIPH2VK5LLUCSN3HM2L2Q4TNSRGPMD4ZFQOB4YLLKE4NTLQ --source cashabroad --network testnet -- total_final_associations
\ No newline at end of file
+## Get the admin and the proposed one
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- admin
+soroban contract invoke --id $MANAGER --source cashabroad --network testnet -- pending_admin
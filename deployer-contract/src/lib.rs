@@ -1,561 +1,2718 @@
 #![no_std]
 
+#[cfg(test)]
+extern crate std;
+
 use num_integer::Roots;
 
 use soroban_sdk::{
-  contract, contracterror, contractimpl, contracttype, token, log,
-  Address, Env, Vec, IntoVal, Val,
+  contract, contractclient, contractimpl, contracttype, token, log, symbol_short,
+  xdr::ToXdr, Address, Bytes, BytesN, Env, Map, Vec, IntoVal, Val,
+};
+
+use shared::{
+  get_page_range, CommitRevealConfig, Commitment, ContributionLimits, DepositMemo, Error, FinalAssociation, MatchingConfig,
+  MatchingMode, Memo, Pledge, Role, RoundCounts, State, StorageConst, TokenConfig, TokenDonation, TtlConfig,
+  BUMP_HIGH, BUMP_LOW, DONOR_CHUNK_SIZE, EVENTS_VERSION, MAX_PAGE_SIZE, MAX_TTL,
 };
 
-#[contracterror]
-#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
-#[repr(u32)]
-pub enum Error{
-  InvalidAuth = 1,
-  KeyExpected = 2,
-  ExpectedExtraValue = 3,
-  InvalidAmount = 4,
-  AlreadyWithdrawn = 5,
-  InvalidAssociation = 6,
-  InvalidTimestamp = 7,
-  AlreadyInitialized = 8,
+//Highest number of decimal digits accepted for the fixed-point calculations.
+const MAX_PRECISION: u32 = 9;
+
+//Basis points of a whole amount.
+const MAX_BPS: i128 = 10000;
+
+//Highest length of the memo a donor can attach to a deposit.
+const MAX_MEMO_LEN: u32 = 128;
+
+//Highest number of pledges of a round, every pledge is applied to every association when the funding is calculated.
+const MAX_PLEDGES: u32 = 10;
+
+//Default seconds the funding of a round stays in review, the challenge period can not be 0.
+const DEFAULT_CHALLENGE_PERIOD: u64 = 3 * 24 * 60 * 60;
+
+/*
+The running totals of the donations to an association, updated by every contribution so the funding
+is calculated from a single entry per association:
+direct: the value of every donation in the base token.
+counted: the part of it counted for the matching, only the donations of the attested donors when the round has a verifier.
+sqrt_sum: the sum of the square roots of the counted contribution of each donor, scaled by the fixed-point scale.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssociationTotals {
+  pub direct: i128,
+  pub counted: i128,
+  pub sqrt_sum: i128,
+}
+
+/*
+The progress of the tally of the pairwise weights of a round, built by pages with tally_pairs:
+association: the position in the AssociationIndex of the association whose pairs of donors are tallied.
+donor and other: the positions in its DonorIndex of the next pair of donors to tally.
+weights: the pairwise weight of every association added up so far, by its position in the AssociationIndex.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PairTally {
+  pub association: u32,
+  pub donor: u32,
+  pub other: u32,
+  pub weights: Vec<i128>,
 }
 
+/*
+The part of the matching pool the associations did not get, returned to the sponsors by pages after the withdraw:
+amount: the total to return, each sponsor gets its share of it and the rounding dust goes to the last one.
+returned: what has been sent back so far.
+next: the position of the next sponsor to pay.
+*/
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Association{
-  name: Address,
-  contribution: Vec<i64>,
+pub struct UnmatchedPool {
+  pub amount: i128,
+  pub returned: i128,
+  pub next: u32,
 }
 
+//The price of a token returned by the oracle, scaled by 10^decimals, and when it was published.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FinalAssociation{
-  name: Address,
-  contribution: i128,
-  has_withdrawn: bool,
+pub struct PriceData {
+  pub price: i128,
+  pub timestamp: u64,
 }
 
-impl Association{
-  //The method to add a new contribution to a certain address
-  fn add_contribution(&mut self, amount: i64){
-    self.contribution.push_back(amount);
-  }
-  fn get_name(&self) -> &Address {
-    &self.name
+fn set_persistent<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(e: &Env, key: &K, value: &V) {
+  let config: TtlConfig = get_ttl_config(e);
+  e.storage().persistent().set(key, value);
+  e.storage().persistent().bump(key, config.low, config.high);
+}
+
+//An optional setting of a round is kept in instance storage while it is set.
+fn set_optional<V: IntoVal<Env, Val>>(e: &Env, key: &StorageConst, value: Option<V>) {
+  match value {
+      Some(value) => e.storage().instance().set(key, &value),
+      None => e.storage().instance().remove(key),
   }
-  // a function to return the contribution of Association
-  fn get_contribution(&self) -> &Vec<i64> {
-    &self.contribution
+}
+
+fn bump_persistent<K: IntoVal<Env, Val>>(e: &Env, key: &K, config: &TtlConfig) {
+  if e.storage().persistent().has(key) {
+    e.storage().persistent().bump(key, config.low, config.high);
   }
 }
 
-impl FinalAssociation{
-  fn get_contribution(&self) -> i128 {
-    self.contribution
+fn bump_instance(e: &Env) {
+  let config: TtlConfig = get_ttl_config(e);
+  e.storage().instance().bump(config.low, config.high);
+}
+
+/*
+Function to bump the persistent entries of a round: its indexes and counts, and a page of its associations,
+along with the final associations, the commitments and the entries of the lists of the round at the same positions.
+The donors and the memos of each association are bumped by pages of their own (see bump_association).
+*/
+fn bump_round(e: &Env, round_id: u32, offset: u32, limit: u32) {
+  let config: TtlConfig = get_ttl_config(e);
+  bump_persistent(e, &StorageConst::AssociationIndex(round_id), &config);
+  bump_persistent(e, &StorageConst::FinalAssociationCount(round_id), &config);
+  bump_persistent(e, &StorageConst::Counts(round_id), &config);
+  bump_persistent(e, &StorageConst::PairTally(round_id), &config);
+  bump_persistent(e, &StorageConst::Unmatched(round_id), &config);
+  let counts: RoundCounts = get_round_counts(e, round_id);
+  for i in 0..counts.pledges {
+      bump_persistent(e, &StorageConst::Pledge(round_id, i), &config);
+  }
+  let (start, end) = get_page_range(counts.sponsors, offset, limit);
+  for i in start..end {
+      bump_persistent(e, &StorageConst::SponsorIndex(round_id, i), &config);
+      bump_persistent(e, &StorageConst::Sponsor(round_id, get_sponsor(e, round_id, i)), &config);
+  }
+  let (start, end) = get_page_range(get_final_association_count(e, round_id), offset, limit);
+  for i in start..end {
+      bump_persistent(e, &StorageConst::FinalAssociation(round_id, i), &config);
+  }
+  let (start, end) = get_page_range(counts.committers, offset, limit);
+  for i in start..end {
+      bump_persistent(e, &StorageConst::Committer(round_id, i), &config);
+      bump_persistent(e, &StorageConst::Commitment(round_id, get_committer(e, round_id, i)), &config);
   }
-  fn set_contribution(&mut self, amount: i128){
-    self.contribution = amount;
+  let index: Vec<Address> = get_association_index(e, round_id);
+  let (start, end) = get_page_range(index.len(), offset, limit);
+  for association in index.slice(start..end).iter() {
+      bump_persistent(e, &StorageConst::AssociationSuspended(round_id, association.clone()), &config);
+      bump_persistent(e, &StorageConst::DonorCount(round_id, association.clone()), &config);
+      bump_persistent(e, &StorageConst::AssociationTotals(round_id, association.clone()), &config);
+      bump_persistent(e, &StorageConst::AssociationTokens(round_id, association.clone()), &config);
+      bump_persistent(e, &StorageConst::MemoCount(round_id, association.clone()), &config);
   }
 }
-#[contracttype]
-pub enum StorageConst {
-    AdminAddress,
-    Associations,
-    FinalAssociations,
-    Deadline,
-    RecipientsClaimed,
-    AssetAdress,
-    State,
-    TotalAmount,
-    ContractCallAddress,
+
+/*
+Function to bump a page of the donors of an association, with their donations, attestations and donations in the accepted tokens,
+and the memos of the association at the same positions. It also works for a removed association whose donors are not all refunded.
+*/
+fn bump_association(e: &Env, round_id: u32, association: &Address, offset: u32, limit: u32) {
+  let config: TtlConfig = get_ttl_config(e);
+  let tokens: Vec<Address> = get_accepted_tokens(e, round_id);
+  let (start, end) = get_page_range(get_donor_count(e, round_id, association), offset, limit);
+  for chunk in start / DONOR_CHUNK_SIZE..end.div_ceil(DONOR_CHUNK_SIZE) {
+      bump_persistent(e, &StorageConst::DonorIndex(round_id, association.clone(), chunk), &config);
+  }
+  for i in start..end {
+      let donor: Address = get_donor_at(e, round_id, association, i);
+      bump_persistent(e, &StorageConst::Donations(round_id, donor.clone()), &config);
+      bump_persistent(e, &StorageConst::Attested(round_id, donor.clone()), &config);
+      for asset in tokens.iter() {
+          bump_persistent(e, &StorageConst::TokenContribution(round_id, association.clone(), donor.clone(), asset), &config);
+      }
+  }
+  let (start, end) = get_page_range(get_memo_count(e, round_id, association), offset, limit);
+  for i in start..end {
+      bump_persistent(e, &StorageConst::Memo(round_id, association.clone(), i), &config);
+  }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-#[repr(u32)]
-pub enum State {
-    Running = 0,
-    Ended = 1,
+fn init_associations(e: &Env, round_id: u32, associations: &Vec<Address>) -> Result<(), Error> {
+  set_persistent(e, &StorageConst::AssociationIndex(round_id), &Vec::<Address>::new(e));
+  for association in associations.iter() {
+      add_new_association(e, round_id, &association)?;
+  }
+  Ok(())
 }
 
-/*Function to solve "the trait bound `State: TryFromVal<Env, soroban_sdk::Val>` is not satisfied
-the following other types implement trait `TryFromVal<E, V>`:"*/
+/*
+Function to add an association to a round, nothing changes if it is already part of it.
+A removed association can only be added again once its donations are all refunded.
+*/
+fn add_new_association(e: &Env, round_id: u32, name: &Address) -> Result<(), Error> {
+  if is_association(e, round_id, name) {
+      return Ok(());
+  }
+  if e.storage().persistent().has(&StorageConst::DonorCount(round_id, name.clone())) {
+      log!(
+        e,
+        "Something went wrong, the donations of the association have not all been refunded since it was removed."
+      );
+      return Err(Error::RefundsPending);
+  }
+  let mut index: Vec<Address> = get_association_index(e, round_id);
+  index.push_back(name.clone());
+  set_persistent(e, &StorageConst::AssociationIndex(round_id), &index);
+  set_persistent(e, &StorageConst::AssociationSuspended(round_id, name.clone()), &false);
+  set_persistent(e, &StorageConst::DonorCount(round_id, name.clone()), &0_u32);
+  set_persistent(e, &StorageConst::AssociationTotals(round_id, name.clone()), &AssociationTotals { direct: 0, counted: 0, sqrt_sum: 0 });
+  Ok(())
+}
 
-impl IntoVal<Env, Val> for State {
-  fn into_val(&self, env: &Env) -> Val {
-      (*self as u32).into_val(env)
+/*
+Function to add a new contribution from a donor, aggregated with the previous ones of the same donor,
+and to update the running totals of the association with it.
+The amount is the value of the donation in the base token, the caller transfers the funds.
+*/
+fn add_contribution(e: &Env, round_id: u32, association: &Address, sender: &Address, amount: i128) -> Result<(), Error>{
+  if !is_association(e, round_id, association) {
+      log!(
+        e,
+        "Something went wrong, the association is not part of the contract."
+      );
+      return Err(Error::InvalidAssociation);
+  }
+  if is_suspended(e, round_id, association) {
+      log!(
+        e,
+        "Something went wrong, the association is suspended."
+      );
+      return Err(Error::InvalidAssociation);
+  }
+  let mut donations: Map<Address, i128> = get_donations(e, round_id, sender);
+  check_contribution_limits(e, round_id, &donations, association, amount)?;
+  let contribution: i128 = donations.get(association.clone()).unwrap_or(0);
+  if contribution == 0 {
+      add_donor(e, round_id, association, sender);
+  }
+  donations.set(association.clone(), contribution + amount);
+  set_persistent(e, &StorageConst::Donations(round_id, sender.clone()), &donations);
+  let mut totals: AssociationTotals = get_association_totals(e, round_id, association);
+  totals.direct += amount;
+  if is_counted(e, round_id, sender) {
+      count_contribution(e, round_id, &mut totals, contribution, contribution + amount)?;
   }
+  set_persistent(e, &StorageConst::AssociationTotals(round_id, association.clone()), &totals);
+  Ok(())
 }
 
-fn init_associations(e: &Env, associations: Vec<Address>) -> Vec<Association>{
-  let mut associations_vec: Vec<Association> = Vec::new(&e);
+//Function to replace the counted contribution of a donor in the totals of an association, from before to after.
+fn count_contribution(e: &Env, round_id: u32, totals: &mut AssociationTotals, before: i128, after: i128) -> Result<(), Error> {
+  let scale: i128 = get_scale(e, round_id);
+  totals.counted += after - before;
+  totals.sqrt_sum += fixed_sqrt(after, scale)? - fixed_sqrt(before, scale)?;
+  Ok(())
+}
 
-  for association in associations.iter() {
-      add_new_association(e, &mut associations_vec, &association);
+/*
+Function to add the donations of a donor to the counted totals of the associations it funded, or to take them out
+when counted is false, once its attestation starts or stops being valid at the deadline.
+*/
+fn recount_donor(e: &Env, round_id: u32, donor: &Address, counted: bool) -> Result<(), Error> {
+  for (association, amount) in get_donations(e, round_id, donor).iter() {
+      if !is_association(e, round_id, &association) {
+          continue;
+      }
+      let mut totals: AssociationTotals = get_association_totals(e, round_id, &association);
+      if counted {
+          count_contribution(e, round_id, &mut totals, 0, amount)?;
+      } else {
+          count_contribution(e, round_id, &mut totals, amount, 0)?;
+      }
+      set_persistent(e, &StorageConst::AssociationTotals(round_id, association), &totals);
   }
-  associations_vec
+  Ok(())
 }
 
-fn add_new_association(e: &Env, associations: &mut Vec<Association>, name: &Address) {
-  let new_assoc: Association = Association {
-      name: name.clone(),
-      contribution: Vec::new(&e),
+/*
+Function to check a new contribution against the contribution limits of the round: it has to be at least min_deposit,
+and the donor can not go over max_per_association to the association or over max_per_donor to the whole round.
+donations are the previous donations of the donor to each association.
+*/
+fn check_contribution_limits(e: &Env, round_id: u32, donations: &Map<Address, i128>, association: &Address, amount: i128) -> Result<(), Error> {
+  let limits: ContributionLimits = match get_contribution_limits(e, round_id) {
+      Some(limits) => limits,
+      None => return Ok(()),
   };
-  associations.push_back(new_assoc);
+  if amount < limits.min_deposit {
+      log!(
+        e,
+        "Something went wrong, the amount is less than the minimum deposit."
+      );
+      return Err(Error::InvalidContribution);
+  }
+  if limits.max_per_association > 0 && donations.get(association.clone()).unwrap_or(0) + amount > limits.max_per_association {
+      log!(
+        e,
+        "Something went wrong, the donor would give more than the maximum to the association."
+      );
+      return Err(Error::InvalidContribution);
+  }
+  let mut total: i128 = amount;
+  for donation in donations.values().iter() {
+      total += donation;
+  }
+  if limits.max_per_donor > 0 && total > limits.max_per_donor {
+      log!(
+        e,
+        "Something went wrong, the donor would give more than the maximum to the round."
+      );
+      return Err(Error::InvalidContribution);
+  }
+  Ok(())
 }
 
-fn get_associations_address(e: &Env, associations: Vec<Association>) -> Vec<Address>{
-  // iterate trough this vector of associations and return the addresses as a new vector
-  let mut addresses: Vec<Address> = Vec::new(&e);
-  for association in associations.iter() {
-      addresses.push_back(association.get_name().clone());
-  }
-  addresses
+fn add_commitment(e: &Env, round_id: u32, donor: &Address, hash: &BytesN<32>, amount: i128) -> Commitment {
+  let (committed, position): (i128, u32) = match get_commitment(e, round_id, donor) {
+      Some(commitment) => (commitment.amount, commitment.position),
+      None => {
+          let mut counts: RoundCounts = get_round_counts(e, round_id);
+          set_persistent(e, &StorageConst::Committer(round_id, counts.committers), donor);
+          counts.committers += 1;
+          set_persistent(e, &StorageConst::Counts(round_id), &counts);
+          (0, counts.committers - 1)
+      }
+  };
+  let commitment: Commitment = Commitment { hash: hash.clone(), amount: committed + amount, position };
+  set_persistent(e, &StorageConst::Commitment(round_id, donor.clone()), &commitment);
+  commitment
 }
 
-fn get_amount_per_association(e: &Env, association: &Vec<Association>, name: &Address) -> Vec<i64>{
-  let mut amounts: Vec<i64> = Vec::new(&e);
-  for assoc in association.iter() {
-      if assoc.get_name() == name {
-        amounts = assoc.get_contribution().clone();
+//Function to take a revealed commitment out of the committers, the last committer takes its place.
+fn remove_committer(e: &Env, round_id: u32, position: u32) {
+  let mut counts: RoundCounts = get_round_counts(e, round_id);
+  counts.committers -= 1;
+  if position != counts.committers {
+      let last: Address = get_committer(e, round_id, counts.committers);
+      let key: StorageConst = StorageConst::Commitment(round_id, last.clone());
+      if let Some(mut commitment) = e.storage().persistent().get::<_, Commitment>(&key) {
+          commitment.position = position;
+          set_persistent(e, &key, &commitment);
       }
+      set_persistent(e, &StorageConst::Committer(round_id, position), &last);
   }
-  amounts
+  e.storage().persistent().remove(&StorageConst::Committer(round_id, counts.committers));
+  set_persistent(e, &StorageConst::Counts(round_id), &counts);
 }
 
-fn add_contribution(e: &Env, associations: &mut Vec<Association>, association: &Address, sender: &Address,amount: i64) -> Vec<Association>{
-  let contract_token = e.current_contract_address();
-  let amount_i128: i128 = amount as i128;
-  let mut i:u32 = 0;
-  for mut assoc in associations.iter() {
-      if assoc.get_name() == association {
-          assoc.add_contribution(amount);
-          associations.set(i, assoc);
-          transfer(e, sender, &contract_token, &amount_i128);
+/*
+Function to reveal the commitment of a donor: the allocations are checked against the hash and added as contributions.
+The amounts allocated to associations removed or suspended since the commitment, or over the contribution limits,
+are sent back to the donor,
+it returns the amount added as contributions and the amount sent back.
+*/
+fn reveal_commitment(e: &Env, round_id: u32, donor: &Address, allocations: &Map<Address, i128>, salt: &BytesN<32>) -> Result<(i128, i128), Error> {
+  let commitment: Commitment = match get_commitment(e, round_id, donor) {
+      Some(commitment) => commitment,
+      None => {
+          log!(
+            e,
+            "Something went wrong, the donor has no commitment to reveal."
+          );
+          return Err(Error::InvalidCommitment);
+      }
+  };
+  let hash: BytesN<32> = e.crypto().sha256(&(e.current_contract_address(), round_id, donor.clone(), allocations.clone(), salt.clone()).to_xdr(e));
+  let mut total: i128 = 0;
+  for amount in allocations.values().iter() {
+      if amount <= 0 {
+          log!(
+            e,
+            "Something went wrong, the amount is 0 or less than 0."
+          );
+          return Err(Error::InvalidAmount);
+      }
+      total = total.checked_add(amount).ok_or(Error::Overflow)?;
+  }
+  if hash != commitment.hash || total != commitment.amount {
+      log!(
+        e,
+        "Something went wrong, the allocations do not match the commitment."
+      );
+      return Err(Error::InvalidCommitment);
+  }
+  let mut revealed: i128 = 0;
+  let mut returned: i128 = 0;
+  for (association, amount) in allocations.iter() {
+      if is_association(e, round_id, &association) && !is_suspended(e, round_id, &association)
+          && check_contribution_limits(e, round_id, &get_donations(e, round_id, donor), &association, amount).is_ok() {
+          add_contribution(e, round_id, &association, donor, amount)?;
+          revealed += amount;
+      } else {
+          returned += amount;
       }
-      i += 1;
   }
-  associations.clone()
+  if returned > 0 {
+      transfer(e, round_id, &e.current_contract_address(), donor, &returned);
+  }
+  e.storage().persistent().remove(&StorageConst::Commitment(round_id, donor.clone()));
+  remove_committer(e, round_id, commitment.position);
+  Ok((revealed, returned))
 }
 
-fn transfer(e: &Env, from: &Address, to: &Address, amount: &i128) {
-  let token_contract_id: &Address = &get_token_address(e);
-  let client = token::Client::new(e, token_contract_id);
-  client.transfer(from, to, amount);
+/*
+Function to settle up to limit commitments not revealed before the reveal deadline, in the order of the committers,
+they are sent back to their donors or added to the matching pool depending on the commit-reveal config.
+A forfeited commitment has no sponsor, so the forfeiting donor gets none of the matching pool back,
+it is only kept to be refunded if the round is cancelled. It returns how many are left to settle.
+*/
+fn settle_commitments(e: &Env, round_id: u32, config: &CommitRevealConfig, limit: u32) -> u32 {
+  let mut counts: RoundCounts = get_round_counts(e, round_id);
+  let (start, end) = get_page_range(counts.committers, counts.settled, limit);
+  let mut forfeited: i128 = 0;
+  for i in start..end {
+      let donor: Address = get_committer(e, round_id, i);
+      if let Some(commitment) = get_commitment(e, round_id, &donor) {
+          if config.forfeit {
+              forfeited += commitment.amount;
+          } else {
+              transfer(e, round_id, &e.current_contract_address(), &donor, &commitment.amount);
+              e.storage().persistent().remove(&StorageConst::Commitment(round_id, donor.clone()));
+          }
+          e.events().publish((symbol_short!("unreveal"), EVENTS_VERSION, round_id), (donor, commitment.amount, config.forfeit));
+      }
+  }
+  if forfeited > 0 {
+      let matching_pool: i128 = get_matching_pool(e, round_id) + forfeited;
+      e.storage().instance().set(&StorageConst::MatchingPool(round_id), &matching_pool);
+      e.storage().instance().set(&StorageConst::Forfeited(round_id), &(get_forfeited(e, round_id) + forfeited));
+  }
+  counts.settled = end;
+  set_persistent(e, &StorageConst::Counts(round_id), &counts);
+  counts.committers - end
 }
 
-fn duplicate(e: &Env, associations: &Vec<FinalAssociation>, sender: &Address) -> Vec<FinalAssociation>{
-  let contract_address = e.current_contract_address();
-  for mut association in associations.iter() {
-      let mut amount = association.get_contribution();
-      transfer(e, sender, &contract_address, &amount);
-      amount = amount * 2;
-      association.set_contribution(amount);
+/*
+Function to keep the memo of a deposit in a new entry after the memos of the previous deposits to the association.
+It returns the memo to publish in the deposit event, only a public one.
+*/
+fn add_memo(e: &Env, round_id: u32, association: &Address, sender: &Address, memo: &Option<Memo>) -> Result<Option<Memo>, Error> {
+  let memo: Memo = match memo {
+      Some(memo) => memo.clone(),
+      None => return Ok(None),
+  };
+  if memo.text.len() > MAX_MEMO_LEN {
+      log!(
+        e,
+        "Something went wrong, the memo is longer than the maximum allowed."
+      );
+      return Err(Error::InvalidMetadata);
   }
-  associations.clone()
+  let count: u32 = get_memo_count(e, round_id, association);
+  set_persistent(e, &StorageConst::Memo(round_id, association.clone(), count), &DepositMemo { donor: sender.clone(), memo: memo.clone() });
+  set_persistent(e, &StorageConst::MemoCount(round_id, association.clone()), &(count + 1));
+  Ok(if memo.public { Some(memo) } else { None })
 }
 
-  /*
-  Function to calculate the funding for each association.
-  */
-fn calculate_funding(e: &Env, associations: &mut Vec<Association>) -> Vec<FinalAssociation>{
-  //Implement the quadratic funding for this function.
-  let mut final_associations: Vec<FinalAssociation> = Vec::new(&e);
-  let mut total_square_sum:i128 = 0;
+fn get_memo_count(e: &Env, round_id: u32, association: &Address) -> u32 {
+  e.storage()
+    .persistent()
+    .get::<_, u32>(&StorageConst::MemoCount(round_id, association.clone()))
+    .unwrap_or(0)
+}
 
-  for association in associations.iter() {
-      let mut sqrt_sum_for_association: i128 = 0;
-      for contribution in association.get_contribution().iter() {
-          sqrt_sum_for_association += (contribution as i128).sqrt();
+//Function to record a donation made in an accepted token, along with its value in the base token.
+fn add_token_contribution(e: &Env, round_id: u32, association: &Address, sender: &Address, asset: &Address, donation: &TokenDonation) {
+  let key: StorageConst = StorageConst::TokenContribution(round_id, association.clone(), sender.clone(), asset.clone());
+  let mut donor_donation: TokenDonation = e.storage()
+      .persistent()
+      .get::<_, TokenDonation>(&key)
+      .unwrap_or(TokenDonation{amount: 0, value: 0});
+  donor_donation.amount += donation.amount;
+  donor_donation.value += donation.value;
+  set_persistent(e, &key, &donor_donation);
+  let mut totals: Map<Address, TokenDonation> = get_association_tokens(e, round_id, association);
+  let mut total: TokenDonation = totals.get(asset.clone()).unwrap_or(TokenDonation{amount: 0, value: 0});
+  total.amount += donation.amount;
+  total.value += donation.value;
+  totals.set(asset.clone(), total);
+  set_persistent(e, &StorageConst::AssociationTokens(round_id, association.clone()), &totals);
+}
+
+/*
+Function to refund a donor every donation made to an association in the accepted tokens.
+It returns their value in the base token, so the caller only refunds the rest in the base token.
+*/
+fn refund_token_contributions(e: &Env, round_id: u32, association: &Address, donor: &Address) -> i128 {
+  let mut value: i128 = 0;
+  let mut totals: Map<Address, TokenDonation> = get_association_tokens(e, round_id, association);
+  for asset in get_accepted_tokens(e, round_id).iter() {
+      let key: StorageConst = StorageConst::TokenContribution(round_id, association.clone(), donor.clone(), asset.clone());
+      if let Some(donation) = e.storage().persistent().get::<_, TokenDonation>(&key) {
+          token::Client::new(e, &asset).transfer(&e.current_contract_address(), donor, &donation.amount);
+          e.storage().persistent().remove(&key);
+          if let Some(mut total) = totals.get(asset.clone()) {
+              total.amount -= donation.amount;
+              total.value -= donation.value;
+              totals.set(asset, total);
+          }
+          value += donation.value;
       }
-      total_square_sum += powi(sqrt_sum_for_association,2);
   }
-
-  for association in associations.iter() {
-      let total_amount: i128;
-      total_amount = calculate_quadratic_funding_for_association(&association.get_contribution(), total_square_sum, get_total(&e));
-      let final_assoc: FinalAssociation = FinalAssociation{
-          name: association.get_name().clone(),
-          contribution: total_amount,
-          has_withdrawn: false,
-      };
-      final_associations.push_back(final_assoc);
+  if value > 0 {
+      set_persistent(e, &StorageConst::AssociationTokens(round_id, association.clone()), &totals);
   }
-  final_associations
+  value
 }
+
 /*
-Function to calculate the quadratic funding for a certain association.
+Function to pay an association the donations it received in the accepted tokens, in the token they were made.
+It returns their value in the base token, which is taken out of the funding handed to the distribution contract.
 */
-fn calculate_quadratic_funding_for_association(contributions: &Vec<i64>, total_square_sum: i128, total_funding: i128) -> i128 {
-  let mut sqrt_sum_for_association:i128 = 0;
-  for contribution in contributions.iter() {
-      sqrt_sum_for_association += (contribution as i128).sqrt();
+fn pay_token_contributions(e: &Env, round_id: u32, association: &FinalAssociation) -> i128 {
+  let mut value: i128 = 0;
+  for (asset, total) in get_association_tokens(e, round_id, &association.name).iter() {
+      if total.amount > 0 {
+          token::Client::new(e, &asset).transfer(&e.current_contract_address(), &association.name, &total.amount);
+      }
+      value += total.value;
   }
-  
-  let project_square_sum: i128 = powi(sqrt_sum_for_association,2);
-  let fraction_of_total: i128 = project_square_sum / total_square_sum;
-  fraction_of_total * total_funding
+  value
 }
 
-fn powi(base: i128, exponent: i64) -> i128 {
-  if exponent == 0 {
-      return 1;
+/*
+Function to get the value of an amount of an accepted token in the base token of the round, with the price of the oracle.
+The price has to be positive and not older than the max_price_age of the round.
+*/
+fn get_token_value(e: &Env, round_id: u32, asset: &Address, amount: i128) -> Result<i128, Error> {
+  let config: TokenConfig = match get_token_config(e, round_id) {
+      Some(config) if config.tokens.contains(asset) => config,
+      _ => {
+          log!(
+            e,
+            "Something went wrong, the token is not accepted by the round."
+          );
+          return Err(Error::InvalidToken);
+      }
+  };
+  let oracle = OracleClient::new(e, &config.oracle);
+  let price: PriceData = match oracle.lastprice(&get_token_address(e, round_id), asset) {
+      Some(price) if price.price > 0 && price.timestamp.saturating_add(config.max_price_age) >= get_ledger_timestamp(e) => price,
+      _ => {
+          log!(
+            e,
+            "Something went wrong, the oracle has no recent price for the token."
+          );
+          return Err(Error::InvalidPrice);
+      }
+  };
+  let scale: i128 = 10_i128.checked_pow(oracle.decimals()).ok_or(Error::Overflow)?;
+  let value: i128 = mul_div(amount, price.price, scale)?;
+  if value <= 0 {
+      log!(
+        e,
+        "Something went wrong, the amount is worth nothing in the base token."
+      );
+      return Err(Error::InvalidAmount);
   }
-  if exponent == 1 {
-      return base;
+  Ok(value)
+}
+
+/*
+Function to remove every contribution of a donor to a round, used to refund the donor when the round is cancelled.
+The donor stays in the DonorIndex of the associations since a cancelled round is never paid out.
+The donations in the accepted tokens are refunded right away, it returns the value of everything removed
+and the part of it to refund in the base token.
+*/
+fn remove_contributions(e: &Env, round_id: u32, donor: &Address) -> (i128, i128) {
+  let mut removed: i128 = 0;
+  let mut refund: i128 = 0;
+  for (association, amount) in get_donations(e, round_id, donor).iter() {
+      removed += amount;
+      refund += amount - refund_token_contributions(e, round_id, &association, donor);
   }
+  e.storage().persistent().remove(&StorageConst::Donations(round_id, donor.clone()));
+  (removed, refund)
+}
 
-  let mut result = 1;
-  let mut current_base = base;
-  let mut current_exponent = exponent;
+/*
+Function to take an association out of a round, its donations are refunded afterwards by pages (see refund_donors).
+It returns the number of donors to refund.
+*/
+fn remove_association(e: &Env, round_id: u32, association: &Address) -> Result<u32, Error> {
+  if !is_association(e, round_id, association) {
+      log!(
+        e,
+        "Something went wrong, the association is not part of the contract."
+      );
+      return Err(Error::InvalidAssociation);
+  }
+  let mut index: Vec<Address> = get_association_index(e, round_id);
+  if let Some(i) = index.first_index_of(association) {
+      index.remove(i);
+  }
+  set_persistent(e, &StorageConst::AssociationIndex(round_id), &index);
+  e.storage().persistent().remove(&StorageConst::AssociationSuspended(round_id, association.clone()));
+  e.storage().persistent().remove(&StorageConst::AssociationTotals(round_id, association.clone()));
+  let donors: u32 = get_donor_count(e, round_id, association);
+  if donors == 0 {
+      remove_refunded_association(e, round_id, association);
+  }
+  Ok(donors)
+}
 
-  while current_exponent > 0 {
-      if current_exponent % 2 == 1 {
-          result *= current_base;
+/*
+Function to refund up to limit donors of a removed or suspended association everything they gave to it, in the token they gave it,
+starting from the last donor of its DonorIndex. The refunded donors are taken out of the index so it only happens once,
+it returns how many donors are left to refund.
+*/
+fn refund_donors(e: &Env, round_id: u32, association: &Address, limit: u32) -> u32 {
+  let mut count: u32 = get_donor_count(e, round_id, association);
+  let mut refunded: i128 = 0;
+  for _ in 0..limit.min(MAX_PAGE_SIZE) {
+      if count == 0 {
+          break;
       }
-      current_base *= current_base;
-      current_exponent /= 2;
+      count -= 1;
+      let key: StorageConst = StorageConst::DonorIndex(round_id, association.clone(), count / DONOR_CHUNK_SIZE);
+      let mut donors: Vec<Address> = get_donor_chunk(e, round_id, association, count / DONOR_CHUNK_SIZE);
+      let donor: Address = donors.pop_back_unchecked();
+      if donors.is_empty() {
+          e.storage().persistent().remove(&key);
+      } else {
+          set_persistent(e, &key, &donors);
+      }
+      let mut donations: Map<Address, i128> = get_donations(e, round_id, &donor);
+      let contribution: i128 = donations.get(association.clone()).unwrap_or(0);
+      donations.remove(association.clone());
+      if donations.is_empty() {
+          e.storage().persistent().remove(&StorageConst::Donations(round_id, donor.clone()));
+      } else {
+          set_persistent(e, &StorageConst::Donations(round_id, donor.clone()), &donations);
+      }
+      let amount: i128 = contribution - refund_token_contributions(e, round_id, association, &donor);
+      if amount > 0 {
+          transfer(e, round_id, &e.current_contract_address(), &donor, &amount);
+      }
+      refunded += amount;
+      e.events().publish((symbol_short!("refund"), EVENTS_VERSION, round_id), (donor, amount));
   }
-
-  result
+  let total_amount: i128 = get_total(e, round_id) - refunded;
+  e.storage().instance().set(&StorageConst::TotalAmount(round_id), &total_amount);
+  if count == 0 && !is_association(e, round_id, association) {
+      remove_refunded_association(e, round_id, association);
+  } else {
+      set_persistent(e, &StorageConst::DonorCount(round_id, association.clone()), &count);
+  }
+  count
 }
 
-fn withdraw(e: &Env) {
-  let contract_transfer = get_contract_call_address(e);
-  let total_amount = get_amount(e);
-  transfer(e, &e.current_contract_address(), &contract_transfer, &total_amount);
+//Function to forget the last entries of a removed association once its donations are all refunded, so it can be added again.
+fn remove_refunded_association(e: &Env, round_id: u32, association: &Address) {
+  e.storage().persistent().remove(&StorageConst::DonorCount(round_id, association.clone()));
+  e.storage().persistent().remove(&StorageConst::MemoCount(round_id, association.clone()));
+  e.storage().persistent().remove(&StorageConst::AssociationTokens(round_id, association.clone()));
 }
 
-fn get_ledger_timestamp(e: &Env) -> u64 {
-  e.ledger().timestamp()
+//Function to suspend or resume an association of a round.
+fn set_association_suspended(e: &Env, round_id: u32, association: &Address, suspended: bool) -> Result<(), Error> {
+  if !is_association(e, round_id, association) {
+      log!(
+        e,
+        "Something went wrong, the association is not part of the contract."
+      );
+      return Err(Error::InvalidAssociation);
+  }
+  set_persistent(e, &StorageConst::AssociationSuspended(round_id, association.clone()), &suspended);
+  Ok(())
 }
 
-fn get_deadline(e: &Env) -> u64 {
+fn is_association(e: &Env, round_id: u32, association: &Address) -> bool {
   e.storage()
-      .instance()
-      .get::<_, u64>(&StorageConst::Deadline)
-      .expect("not initialized yet")
+      .persistent()
+      .has(&StorageConst::AssociationSuspended(round_id, association.clone()))
 }
 
-fn get_associations(e: &Env) -> Vec<Association> {
-  e.storage()
-    .instance()
-    .get::<_, Vec<Association>>(&StorageConst::Associations)
-    .expect("not initialized yet")
+//The challenges are filed and resolved in the manager contract, which keeps their count.
+fn has_unresolved_challenges(e: &Env, round_id: u32) -> bool {
+  ManagerClient::new(e, &get_manager(e)).open_challenges(&round_id) > 0
 }
 
-fn get_final_associations(e: &Env) -> Vec<FinalAssociation> {
-  e.storage()
-    .instance()
-    .get::<_, Vec<FinalAssociation>>(&StorageConst::FinalAssociations)
-    .expect("not initialized yet")
+/*
+Function to discard the computed funding of a round after its associations change,
+the round goes back to Ended so calculate_funding and end_funding have to be called again, the donations stay closed.
+*/
+fn reset_final_associations(e: &Env, round_id: u32) {
+  set_final_associations(e, round_id, &Vec::<FinalAssociation>::new(e));
+  let state: State = get_state(e, round_id);
+  if state == State::Calculated || state == State::Review {
+    set_state(e, round_id, State::Ended);
+  }
 }
 
-fn get_token_address(e: &Env) -> Address {
-  e.storage()
-      .instance()
-      .get::<_, Address>(&StorageConst::AssetAdress)
-      .expect("not initialized yet")
+//Function to add the amount a sponsor put in the matching pool of a round, a new sponsor is added at the end of the SponsorIndex.
+fn add_sponsor_amount(e: &Env, round_id: u32, sponsor: &Address, amount: i128) {
+  let key: StorageConst = StorageConst::Sponsor(round_id, sponsor.clone());
+  match e.storage().persistent().get::<_, i128>(&key) {
+      Some(sponsor_amount) => set_persistent(e, &key, &(sponsor_amount + amount)),
+      None => {
+          let mut counts: RoundCounts = get_round_counts(e, round_id);
+          set_persistent(e, &StorageConst::SponsorIndex(round_id, counts.sponsors), sponsor);
+          counts.sponsors += 1;
+          set_persistent(e, &StorageConst::Counts(round_id), &counts);
+          set_persistent(e, &key, &amount);
+      }
+  }
 }
 
-fn get_admin_address(e: &Env) -> Address {
-  e.storage()
-      .instance()
-      .get::<_, Address>(&StorageConst::AdminAddress)
-      .expect("not initialized yet")
+/*
+Function to add the matches of the pledges to the calculated funding, on top of the matching pool and its cap.
+Every pledge matches the donations counted for the matching of the associations that are not suspended,
+scaled down in proportion when they need more than its budget. The pledges are stored with what they matched.
+*/
+fn apply_pledges(e: &Env, round_id: u32, final_associations: &mut Vec<FinalAssociation>, totals: &Vec<AssociationTotals>) -> Result<(), Error> {
+  for (index, mut pledge) in get_pledges(e, round_id).iter().enumerate() {
+      let mut matches: Vec<i128> = Vec::new(e);
+      let mut needed: i128 = 0;
+      for (i, association_totals) in totals.iter().enumerate() {
+          let mut amount: i128 = 0;
+          if !final_associations.get_unchecked(i as u32).suspended {
+              amount = mul_div(association_totals.counted, pledge.multiplier_bps as i128, MAX_BPS)?;
+          }
+          needed = needed.checked_add(amount).ok_or(Error::Overflow)?;
+          matches.push_back(amount);
+      }
+      pledge.matched = 0;
+      for (i, mut amount) in matches.iter().enumerate() {
+          if needed > pledge.budget {
+              amount = mul_div(amount, pledge.budget, needed)?;
+          }
+          let mut association: FinalAssociation = final_associations.get_unchecked(i as u32);
+          association.set_matched(association.get_matched() + amount);
+          final_associations.set(i as u32, association);
+          pledge.matched += amount;
+      }
+      set_persistent(e, &StorageConst::Pledge(round_id, index as u32), &pledge);
+  }
+  Ok(())
 }
 
-fn get_total(e: &Env) -> i128 {
-  e.storage()
-      .instance()
-      .get::<_, i128>(&StorageConst::TotalAmount)
-      .expect("not initialized yet")
+//Function to empty the pledge of a sponsor of a cancelled round, it returns the budget to refund.
+fn remove_pledge(e: &Env, round_id: u32, sponsor: &Address) -> i128 {
+  for (i, mut pledge) in get_pledges(e, round_id).iter().enumerate() {
+      if pledge.sponsor == *sponsor {
+          let budget: i128 = pledge.budget;
+          pledge.budget = 0;
+          set_persistent(e, &StorageConst::Pledge(round_id, i as u32), &pledge);
+          return budget;
+      }
+  }
+  0
 }
 
-fn get_state(e: &Env) -> State {
-  let deadline: u64 = get_deadline(e);
-  let current_timestamp: u64 = get_ledger_timestamp(e);
+fn transfer(e: &Env, round_id: u32, from: &Address, to: &Address, amount: &i128) {
+  let token_contract_id: &Address = &get_token_address(e, round_id);
+  let client = token::Client::new(e, token_contract_id);
+  client.transfer(from, to, amount);
+}
 
-  if current_timestamp < deadline {
-      return State::Running;
-  };
-  if current_timestamp >= deadline {
-      return State::Ended;
+/*
+Function to check that nothing can change the donations of a round anymore: the deadline and the reveal deadline are over
+and the commitments not revealed have all been settled, so the funding can be calculated.
+*/
+fn check_donations_closed(e: &Env, round_id: u32) -> Result<(), Error> {
+  if get_ledger_timestamp(e) <= get_deadline(e, round_id) {
+    log!(
+      e,
+      "Something went wrong, the donations are not over."
+    );
+    return Err(Error::InvalidTimestamp);
+  }
+  if let Some(config) = get_commit_reveal(e, round_id) {
+    if get_ledger_timestamp(e) <= config.reveal_deadline {
+      log!(
+        e,
+        "Something went wrong, the commitments can still be revealed."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    let counts: RoundCounts = get_round_counts(e, round_id);
+    if counts.settled < counts.committers {
+      log!(
+        e,
+        "Something went wrong, the commitments not revealed have not all been settled."
+      );
+      return Err(Error::InvalidCommitment);
+    }
+  }
+  Ok(())
+}
+
+/*
+Function to calculate the funding of every association from its running totals, reading one entry per association.
+The pairwise weights come from the tally built beforehand with tally_pairs.
+*/
+fn calculate_funding(e: &Env, round_id: u32, index: &Vec<Address>, totals: &Vec<AssociationTotals>) -> Result<Vec<FinalAssociation>, Error>{
+  let scale: i128 = get_scale(e, round_id);
+  let total_funding: i128 = get_matching_pool(e, round_id);
+  let mut suspended: Vec<bool> = Vec::new(e);
+  for name in index.iter() {
+      suspended.push_back(is_suspended(e, round_id, &name));
+  }
+  let weights: Vec<i128> = match get_matching_mode(e, round_id) {
+      MatchingMode::Quadratic => calculate_quadratic_weights(e, totals, &suspended, scale)?,
+      MatchingMode::Pairwise => get_pairwise_weights(e, round_id, index.len())?,
   };
-  State::Ended
+  let cap: Option<i128> = get_matching_cap(e, round_id, total_funding)?;
+
+  let mut capped: Vec<bool> = Vec::new(e);
+  for _ in weights.iter() {
+      capped.push_back(false);
+  }
+  let mut matched_amounts: Vec<i128> = split_matching_pool(e, &weights, &capped, cap, total_funding)?;
+  if let Some(cap) = cap {
+      loop {
+          let mut new_capped: bool = false;
+          for (i, matched) in matched_amounts.iter().enumerate() {
+              if matched > cap && !capped.get_unchecked(i as u32) {
+                  capped.set(i as u32, true);
+                  new_capped = true;
+              }
+          }
+          if !new_capped {
+              break;
+          }
+          matched_amounts = split_matching_pool(e, &weights, &capped, Some(cap), total_funding)?;
+      }
+  }
+
+  let mut final_associations: Vec<FinalAssociation> = Vec::new(e);
+  let mut distributed: i128 = 0;
+  let mut biggest_weight: i128 = 0;
+  let mut dust_receiver: Option<u32> = None;
+  for (i, name) in index.iter().enumerate() {
+      let weight: i128 = weights.get_unchecked(i as u32);
+      let matched: i128 = matched_amounts.get_unchecked(i as u32);
+      let is_capped: bool = capped.get_unchecked(i as u32);
+      if weight > biggest_weight && !is_capped {
+          biggest_weight = weight;
+          dust_receiver = Some(i as u32);
+      }
+      distributed += matched;
+      let direct: i128 = totals.get_unchecked(i as u32).direct;
+      let final_assoc: FinalAssociation = FinalAssociation{
+          name,
+          contribution: direct + matched,
+          direct,
+          matched,
+          capped: is_capped,
+          suspended: suspended.get_unchecked(i as u32),
+          has_withdrawn: false,
+      };
+      final_associations.push_back(final_assoc);
+  }
+
+  if let Some(dust_receiver) = dust_receiver {
+      let mut receiver: FinalAssociation = final_associations.get_unchecked(dust_receiver);
+      receiver.set_matched(receiver.get_matched() + total_funding - distributed);
+      final_associations.set(dust_receiver, receiver);
+  }
+  Ok(final_associations)
 }
 
-fn get_recipients_claimed(e: &Env) -> bool {
-  e.storage()
-      .instance()
-      .get::<_, bool>(&StorageConst::RecipientsClaimed)
-      .expect("not initialized yet")
+/*
+Function to split the matching pool: the capped associations get the cap and the rest of the pool
+is split proportionally to the weights of the uncapped ones.
+*/
+fn split_matching_pool(e: &Env, weights: &Vec<i128>, capped: &Vec<bool>, cap: Option<i128>, total_funding: i128) -> Result<Vec<i128>, Error> {
+  let mut remaining_funding: i128 = total_funding;
+  let mut remaining_weight: i128 = 0;
+  for (i, weight) in weights.iter().enumerate() {
+      if capped.get_unchecked(i as u32) {
+          remaining_funding -= cap.unwrap_or(0);
+      } else {
+          remaining_weight = remaining_weight.checked_add(weight).ok_or(Error::Overflow)?;
+      }
+  }
+
+  let mut matched_amounts: Vec<i128> = Vec::new(e);
+  for (i, weight) in weights.iter().enumerate() {
+      if capped.get_unchecked(i as u32) {
+          matched_amounts.push_back(cap.unwrap_or(0));
+      } else {
+          matched_amounts.push_back(calculate_quadratic_funding_for_association(weight, remaining_weight, remaining_funding)?);
+      }
+  }
+  Ok(matched_amounts)
 }
 
-fn get_amount(e: &Env) -> i128 {
-  e.storage()
-      .instance()
-      .get::<_, i128>(&StorageConst::TotalAmount)
-      .expect("not initialized yet")
+//Function to get the quadratic weight of every association, (sum of sqrt(donor contribution))^2 with the configured scale.
+fn calculate_quadratic_weights(e: &Env, totals: &Vec<AssociationTotals>, suspended: &Vec<bool>, scale: i128) -> Result<Vec<i128>, Error> {
+  let mut weights: Vec<i128> = Vec::new(e);
+  for (i, association_totals) in totals.iter().enumerate() {
+      if suspended.get_unchecked(i as u32) {
+          weights.push_back(0);
+          continue;
+      }
+      weights.push_back(mul_div(association_totals.sqrt_sum, association_totals.sqrt_sum, scale)?);
+  }
+  Ok(weights)
 }
 
-fn get_contract_call_address(e: &Env) -> Address {
-  e.storage()
-      .instance()
-      .get::<_, Address>(&StorageConst::ContractCallAddress)
-      .expect("not initialized yet")
+//Function to get the pairwise weights of the associations once tally_pairs has gone through every pair of donors.
+fn get_pairwise_weights(e: &Env, round_id: u32, associations: u32) -> Result<Vec<i128>, Error> {
+  let tally: PairTally = get_pair_tally(e, round_id, associations);
+  if tally.association < associations {
+      log!(
+        e,
+        "Something went wrong, the pairs of donors have not all been tallied."
+      );
+      return Err(Error::PairsNotTallied);
+  }
+  Ok(tally.weights)
 }
-#[contract]
-pub struct VotingContract;
+
+/*
+Function to tally the pairwise-bounded weights of the associations by pages of up to limit pairs of donors.
+For every pair of donors (i, j) of an association the subsidy sqrt(c_i) * sqrt(c_j) is multiplied by
+threshold / (threshold + k_ij), where k_ij is the sum of sqrt(c_i) * sqrt(c_j) over all the associations
+both donors funded. Every pair is handled once, in the first association both donors funded (see add_pair_weights).
+It returns the number of associations whose pairs are still to tally.
+*/
+fn tally_pairs(e: &Env, round_id: u32, limit: u32) -> Result<u32, Error> {
+  let index: Vec<Address> = get_association_index(e, round_id);
+  let mut tally: PairTally = get_pair_tally(e, round_id, index.len());
+  let mut pairs: u32 = 0;
+  while tally.association < index.len() && pairs < limit.min(MAX_PAGE_SIZE) {
+      let association: Address = index.get_unchecked(tally.association);
+      let mut donors: u32 = get_donor_count(e, round_id, &association);
+      if is_suspended(e, round_id, &association) {
+          donors = 0;
+      }
+      if tally.donor + 1 >= donors {
+          tally.association += 1;
+          tally.donor = 0;
+          tally.other = 0;
+          continue;
+      }
+      if tally.other <= tally.donor {
+          tally.other = tally.donor + 1;
+      }
+      if tally.other >= donors {
+          tally.donor += 1;
+          continue;
+      }
+      let donor: Address = get_donor_at(e, round_id, &association, tally.donor);
+      let other: Address = get_donor_at(e, round_id, &association, tally.other);
+      add_pair_weights(e, round_id, &index, &mut tally, &donor, &other)?;
+      tally.other += 1;
+      pairs += 1;
+  }
+  set_persistent(e, &StorageConst::PairTally(round_id), &tally);
+  Ok(index.len() - tally.association)
+}
+
+/*
+Function to add the bounded subsidy of a pair of donors to every association both donors funded,
+when the association being tallied is the first of them. The donors not counted for the matching are skipped.
+*/
+fn add_pair_weights(e: &Env, round_id: u32, index: &Vec<Address>, tally: &mut PairTally, donor: &Address, other: &Address) -> Result<(), Error> {
+  if !is_counted(e, round_id, donor) || !is_counted(e, round_id, other) {
+      return Ok(());
+  }
+  let scale: i128 = get_scale(e, round_id);
+  let threshold: i128 = get_pairwise_threshold(e, round_id).checked_mul(scale).ok_or(Error::Overflow)?;
+  let other_donations: Map<Address, i128> = get_donations(e, round_id, other);
+  let mut pair_sqrts: Vec<(u32, i128)> = Vec::new(e);
+  let mut coordination: i128 = 0;
+  for (association, amount) in get_donations(e, round_id, donor).iter() {
+      let Some(other_amount) = other_donations.get(association.clone()) else {
+          continue;
+      };
+      let Some(position) = index.first_index_of(&association) else {
+          continue;
+      };
+      if is_suspended(e, round_id, &association) {
+          continue;
+      }
+      if position < tally.association {
+          return Ok(());
+      }
+      let pair_sqrt: i128 = mul_div(fixed_sqrt(amount, scale)?, fixed_sqrt(other_amount, scale)?, scale)?;
+      coordination = coordination.checked_add(pair_sqrt).ok_or(Error::Overflow)?;
+      pair_sqrts.push_back((position, pair_sqrt));
+  }
+  let divisor: i128 = threshold.checked_add(coordination).ok_or(Error::Overflow)?;
+  for (position, pair_sqrt) in pair_sqrts.iter() {
+      let bounded: i128 = mul_div(pair_sqrt, threshold, divisor)?;
+      let weight: i128 = tally.weights.get_unchecked(position).checked_add(bounded).ok_or(Error::Overflow)?;
+      tally.weights.set(position, weight);
+  }
+  Ok(())
+}
+
+/*
+Function to calculate the quadratic funding for a certain association.
+The share is computed in a single mul_div so it is only truncated once.
+*/
+fn calculate_quadratic_funding_for_association(weight: i128, total_weight: i128, total_funding: i128) -> Result<i128, Error> {
+  if total_weight == 0 {
+      return Ok(0);
+  }
+  mul_div(total_funding, weight, total_weight)
+}
+
+//Square root of a value, scaled by the fixed-point scale.
+fn fixed_sqrt(value: i128, scale: i128) -> Result<i128, Error> {
+  let scaled_value: i128 = value
+      .checked_mul(scale)
+      .and_then(|v| v.checked_mul(scale))
+      .ok_or(Error::Overflow)?;
+  Ok(scaled_value.sqrt())
+}
+
+/*
+Function to compute a * b / c truncated towards 0, the product is kept in 256 bits so it only overflows
+when the result does not fit in an i128.
+*/
+fn mul_div(a: i128, b: i128, c: i128) -> Result<i128, Error> {
+  let negative: bool = (a < 0) ^ (b < 0) ^ (c < 0);
+  let quotient: u128 = mul_div_u128(a.unsigned_abs(), b.unsigned_abs(), c.unsigned_abs()).ok_or(Error::Overflow)?;
+  let quotient: i128 = i128::try_from(quotient).map_err(|_| Error::Overflow)?;
+  Ok(if negative { -quotient } else { quotient })
+}
+
+//Function to multiply two u128 into a high and a low u128 and divide the result bit by bit, None when the quotient needs more than 128 bits.
+fn mul_div_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+  const LOW: u128 = u64::MAX as u128;
+  let (a_high, a_low) = (a >> 64, a & LOW);
+  let (b_high, b_low) = (b >> 64, b & LOW);
+  let low_low: u128 = a_low * b_low;
+  let middle: u128 = a_high * b_low + (low_low >> 64);
+  let other_middle: u128 = a_low * b_high + (middle & LOW);
+  let mut high: u128 = a_high * b_high + (middle >> 64) + (other_middle >> 64);
+  let low: u128 = (other_middle << 64) | (low_low & LOW);
+  if high >= c {
+      return None;
+  }
+  let mut quotient: u128 = 0;
+  for i in (0..128).rev() {
+      let carry: bool = high >> 127 == 1;
+      high = (high << 1) | ((low >> i) & 1);
+      quotient <<= 1;
+      if carry || high >= c {
+          high = high.wrapping_sub(c);
+          quotient |= 1;
+      }
+  }
+  Some(quotient)
+}
+
+fn powi(base: i128, exponent: u32) -> i128 {
+  if exponent == 0 {
+      return 1;
+  }
+  if exponent == 1 {
+      return base;
+  }
+
+  let mut result = 1;
+  let mut current_base = base;
+  let mut current_exponent = exponent;
+
+  while current_exponent > 0 {
+      if current_exponent % 2 == 1 {
+          result *= current_base;
+      }
+      current_base *= current_base;
+      current_exponent /= 2;
+  }
+
+  result
+}
+
+//The salt of the distribution contract of a round is the round id, so its address is known in advance.
+fn get_distribution_salt(e: &Env, round_id: u32) -> BytesN<32> {
+  let mut salt: [u8; 32] = [0; 32];
+  salt[28..].copy_from_slice(&round_id.to_be_bytes());
+  BytesN::from_array(e, &salt)
+}
+
+/*
+Function to send back to up to limit sponsors their share of the part of the matching pool the associations did not get,
+in proportion to what they sponsored, the forfeited commitments are split among them too and the rounding dust goes to the last one.
+It returns the number of sponsors left to pay.
+*/
+fn return_unmatched(e: &Env, round_id: u32, limit: u32) -> Result<u32, Error> {
+  let Some(mut unmatched) = get_unmatched(e, round_id) else {
+      return Ok(0);
+  };
+  let sponsored: i128 = get_matching_pool(e, round_id) - get_forfeited(e, round_id);
+  let sponsors: u32 = get_round_counts(e, round_id).sponsors;
+  let (start, end) = get_page_range(sponsors, unmatched.next, limit);
+  for i in start..end {
+      let sponsor: Address = get_sponsor(e, round_id, i);
+      let mut share: i128 = mul_div(unmatched.amount, get_sponsor_amount(e, round_id, &sponsor), sponsored)?;
+      if i == sponsors - 1 {
+          share = unmatched.amount - unmatched.returned;
+      }
+      if share > 0 {
+          transfer(e, round_id, &e.current_contract_address(), &sponsor, &share);
+      }
+      unmatched.returned += share;
+      e.events().publish((symbol_short!("unmatched"), EVENTS_VERSION, round_id), (sponsor, share));
+  }
+  unmatched.next = end;
+  if end == sponsors {
+      e.storage().persistent().remove(&StorageConst::Unmatched(round_id));
+  } else {
+      set_persistent(e, &StorageConst::Unmatched(round_id), &unmatched);
+  }
+  Ok(sponsors - end)
+}
+
+/*
+Function to deploy a new distribution contract for a round, send it the funds along with the final associations
+and record its address, so the distribution contract pays out the calculated funding without re-entering it by hand.
+This contract initializes the distribution contract, so it is its admin and the only one that can distribute.
+The treasurer calling withdraw is only granted the treasurer role there, to trigger the monthly withdraw,
+and the admin of this contract is proposed as its admin: once it accepts, it manages the associations
+and receives the part of the contribution of a removed association that has not been paid.
+The donations in the accepted tokens are paid out directly, the distribution contract only pays the base token:
+the total of the final associations. The donations to the suspended associations have to be refunded first with refund_association,
+and the matching the associations did not get is recorded to be returned to the sponsors with return_unmatched,
+it goes to the admin when the matching pool only comes from forfeited commitments.
+*/
+fn withdraw(e: &Env, round_id: u32, admin: &Address) -> Result<(), Error> {
+  let mut final_associations: Vec<FinalAssociation> = get_final_associations(e, round_id);
+  let mut names: Vec<Address> = Vec::new(e);
+  let mut total_amount: i128 = 0;
+  let mut unmatched: i128 = get_matching_pool(e, round_id);
+  for (i, mut association) in final_associations.iter().enumerate() {
+      names.push_back(association.name.clone());
+      unmatched -= association.get_matched();
+      let paid: i128 = if association.suspended {
+          if get_donor_count(e, round_id, &association.name) > 0 {
+              log!(
+                e,
+                "Something went wrong, the donations to a suspended association have not all been refunded."
+              );
+              return Err(Error::RefundsPending);
+          }
+          association.direct
+      } else {
+          pay_token_contributions(e, round_id, &association)
+      };
+      if paid > 0 {
+          association.direct -= paid;
+          association.contribution -= paid;
+          final_associations.set(i as u32, association.clone());
+      }
+      total_amount += association.contribution;
+  }
+  let contract_transfer: Address = e.deployer()
+      .with_current_contract(get_distribution_salt(e, round_id))
+      .deploy(get_distribution_wasm(e));
+  let client = DistributionClient::new(e, &contract_transfer);
+  client.init(&e.current_contract_address(), &get_token_address(e, round_id), &names, &get_ledger_timestamp(e));
+  transfer(e, round_id, &e.current_contract_address(), &contract_transfer, &total_amount);
+  for pledge in get_pledges(e, round_id).iter() {
+      unmatched += pledge.matched;
+      let returned: i128 = pledge.budget - pledge.matched;
+      if returned > 0 {
+          transfer(e, round_id, &e.current_contract_address(), &pledge.sponsor, &returned);
+      }
+      e.events().publish((symbol_short!("unpledged"), EVENTS_VERSION, round_id), (pledge.sponsor, returned));
+  }
+  let admin_address: Address = get_admin_address(e);
+  if unmatched > 0 && get_round_counts(e, round_id).sponsors == 0 {
+      transfer(e, round_id, &e.current_contract_address(), &admin_address, &unmatched);
+      e.events().publish((symbol_short!("unmatched"), EVENTS_VERSION, round_id), (admin_address.clone(), unmatched));
+  } else if unmatched > 0 {
+      set_persistent(e, &StorageConst::Unmatched(round_id), &UnmatchedPool { amount: unmatched, returned: 0, next: 0 });
+  }
+  client.distribute(&e.current_contract_address(), &final_associations);
+  client.grant_role(&e.current_contract_address(), &Role::Treasurer, admin);
+  client.propose_admin(&e.current_contract_address(), &admin_address);
+  e.storage().instance().set(&StorageConst::ContractCallAddress(round_id), &contract_transfer);
+  e.events().publish((symbol_short!("withdraw"), EVENTS_VERSION, round_id), (contract_transfer, total_amount));
+  Ok(())
+}
+
+fn get_ledger_timestamp(e: &Env) -> u64 {
+  e.ledger().timestamp()
+}
+
+fn get_deadline(e: &Env, round_id: u32) -> u64 {
+  e.storage()
+      .instance()
+      .get::<_, u64>(&StorageConst::Deadline(round_id))
+      .expect("not initialized yet")
+}
+
+fn get_association_index(e: &Env, round_id: u32) -> Vec<Address> {
+  e.storage()
+    .persistent()
+    .get::<_, Vec<Address>>(&StorageConst::AssociationIndex(round_id))
+    .expect("not initialized yet")
+}
+
+fn get_donor_count(e: &Env, round_id: u32, association: &Address) -> u32 {
+  e.storage()
+    .persistent()
+    .get::<_, u32>(&StorageConst::DonorCount(round_id, association.clone()))
+    .unwrap_or(0)
+}
+
+fn get_donor_chunk(e: &Env, round_id: u32, association: &Address, chunk: u32) -> Vec<Address> {
+  e.storage()
+    .persistent()
+    .get::<_, Vec<Address>>(&StorageConst::DonorIndex(round_id, association.clone(), chunk))
+    .unwrap_or(Vec::new(e))
+}
+
+//Function to add a donor at the end of the DonorIndex of an association.
+fn add_donor(e: &Env, round_id: u32, association: &Address, donor: &Address) {
+  let count: u32 = get_donor_count(e, round_id, association);
+  let chunk: u32 = count / DONOR_CHUNK_SIZE;
+  let mut donors: Vec<Address> = get_donor_chunk(e, round_id, association, chunk);
+  donors.push_back(donor.clone());
+  set_persistent(e, &StorageConst::DonorIndex(round_id, association.clone(), chunk), &donors);
+  set_persistent(e, &StorageConst::DonorCount(round_id, association.clone()), &(count + 1));
+}
+
+fn get_donations(e: &Env, round_id: u32, donor: &Address) -> Map<Address, i128> {
+  e.storage()
+    .persistent()
+    .get::<_, Map<Address, i128>>(&StorageConst::Donations(round_id, donor.clone()))
+    .unwrap_or(Map::new(e))
+}
+
+fn get_association_totals(e: &Env, round_id: u32, association: &Address) -> AssociationTotals {
+  e.storage()
+    .persistent()
+    .get::<_, AssociationTotals>(&StorageConst::AssociationTotals(round_id, association.clone()))
+    .unwrap_or(AssociationTotals { direct: 0, counted: 0, sqrt_sum: 0 })
+}
+
+//Function to get the donor at a position of the DonorIndex of an association.
+fn get_donor_at(e: &Env, round_id: u32, association: &Address, position: u32) -> Address {
+  get_donor_chunk(e, round_id, association, position / DONOR_CHUNK_SIZE).get_unchecked(position % DONOR_CHUNK_SIZE)
+}
+
+fn is_suspended(e: &Env, round_id: u32, association: &Address) -> bool {
+  e.storage()
+    .persistent()
+    .get::<_, bool>(&StorageConst::AssociationSuspended(round_id, association.clone()))
+    .unwrap_or(false)
+}
+
+fn get_final_association_count(e: &Env, round_id: u32) -> u32 {
+  e.storage()
+    .persistent()
+    .get::<_, u32>(&StorageConst::FinalAssociationCount(round_id))
+    .unwrap_or(0)
+}
+
+fn get_final_association(e: &Env, round_id: u32, i: u32) -> FinalAssociation {
+  e.storage()
+    .persistent()
+    .get::<_, FinalAssociation>(&StorageConst::FinalAssociation(round_id, i))
+    .expect("not initialized yet")
+}
+
+fn get_final_associations(e: &Env, round_id: u32) -> Vec<FinalAssociation> {
+  let mut associations: Vec<FinalAssociation> = Vec::new(e);
+  for i in 0..get_final_association_count(e, round_id) {
+      associations.push_back(get_final_association(e, round_id, i));
+  }
+  associations
+}
+
+//Function to store the calculated funding of a round, one entry per association, and remove the entries left over.
+fn set_final_associations(e: &Env, round_id: u32, associations: &Vec<FinalAssociation>) {
+  for (i, association) in associations.iter().enumerate() {
+      set_persistent(e, &StorageConst::FinalAssociation(round_id, i as u32), &association);
+  }
+  for i in associations.len()..get_final_association_count(e, round_id) {
+      e.storage().persistent().remove(&StorageConst::FinalAssociation(round_id, i));
+  }
+  set_persistent(e, &StorageConst::FinalAssociationCount(round_id), &associations.len());
+}
+
+fn get_token_address(e: &Env, round_id: u32) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&StorageConst::AssetAdress(round_id))
+      .expect("not initialized yet")
+}
+
+fn get_round_count(e: &Env) -> u32 {
+  e.storage()
+      .instance()
+      .get::<_, u32>(&StorageConst::RoundCount)
+      .expect("not initialized yet")
+}
+
+//Function to check that the round exists, the entries of a cancelled round can still be bumped and refunded.
+fn check_round_exists(e: &Env, round_id: u32) -> Result<(), Error> {
+  if round_id >= get_round_count(e) {
+    log!(
+      e,
+      "Something went wrong, the round does not exist."
+    );
+    return Err(Error::InvalidRound);
+  }
+  Ok(())
+}
+
+fn check_round(e: &Env, round_id: u32) -> Result<(), Error> {
+  check_round_exists(e, round_id)?;
+  if is_cancelled(e, round_id) {
+    log!(
+      e,
+      "Something went wrong, the round has been cancelled."
+    );
+    return Err(Error::RoundCancelled);
+  }
+  Ok(())
+}
+
+fn is_cancelled(e: &Env, round_id: u32) -> bool {
+  get_state(e, round_id) == State::Cancelled
+}
+
+//Function to check that the round is in one of the states the endpoint is allowed in.
+fn check_state(e: &Env, round_id: u32, states: &[State]) -> Result<(), Error> {
+  if !states.contains(&get_state(e, round_id)) {
+    log!(
+      e,
+      "Something went wrong, the round is not in the right state for this call."
+    );
+    return Err(Error::InvalidState);
+  }
+  Ok(())
+}
+
+//The counts of the lists of a round start at 0, see RoundCounts.
+fn get_round_counts(e: &Env, round_id: u32) -> RoundCounts {
+  e.storage()
+    .persistent()
+    .get::<_, RoundCounts>(&StorageConst::Counts(round_id))
+    .unwrap_or(RoundCounts { sponsors: 0, pledges: 0, committers: 0, settled: 0 })
+}
+
+/*
+Function to check a role with the manager contract, which keeps the roles (see Role).
+The manager contract itself is allowed everything, it only adds the associations of the approved applications
+and discards the funding challenged with success.
+*/
+fn check_role(e: &Env, address: &Address, role: Role) -> Result<(), Error> {
+  let manager: Address = get_manager(e);
+  if *address != manager && !ManagerClient::new(e, &manager).has_role(&role, address) {
+    log!(
+      e,
+      "Something went wrong, the address does not have the role needed to call the function."
+    );
+    return Err(Error::InvalidAuth);
+  }
+  Ok(())
+}
+
+fn get_challenge_period(e: &Env, round_id: u32) -> u64 {
+  e.storage()
+      .instance()
+      .get::<_, u64>(&StorageConst::ChallengePeriod(round_id))
+      .expect("not initialized yet")
+}
+
+//The review deadline is only set by end_funding.
+fn get_review_deadline(e: &Env, round_id: u32) -> u64 {
+  e.storage()
+      .instance()
+      .get::<_, u64>(&StorageConst::ReviewDeadline(round_id))
+      .unwrap_or(0)
+}
+
+fn get_verifier(e: &Env, round_id: u32) -> Option<BytesN<32>> {
+  e.storage()
+      .instance()
+      .get::<_, BytesN<32>>(&StorageConst::Verifier(round_id))
+}
+
+/*
+A donor is attested when their attestation is still valid at the deadline of the round,
+so the donations are counted the same whenever the funding is calculated.
+*/
+fn is_attested(e: &Env, round_id: u32, donor: &Address) -> bool {
+  e.storage()
+    .persistent()
+    .get::<_, u64>(&StorageConst::Attested(round_id, donor.clone()))
+    .is_some_and(|expiry| expiry >= get_deadline(e, round_id))
+}
+
+/*
+Only the donations of the attested donors are counted for the matching when the round has a verifier,
+the other donations are still paid out as direct donations.
+*/
+fn is_counted(e: &Env, round_id: u32, donor: &Address) -> bool {
+  get_verifier(e, round_id).is_none() || is_attested(e, round_id, donor)
+}
+
+fn get_contribution_limits(e: &Env, round_id: u32) -> Option<ContributionLimits> {
+  e.storage()
+      .instance()
+      .get::<_, ContributionLimits>(&StorageConst::ContributionLimits(round_id))
+}
+
+fn get_pledges(e: &Env, round_id: u32) -> Vec<Pledge> {
+  let mut pledges: Vec<Pledge> = Vec::new(e);
+  for i in 0..get_round_counts(e, round_id).pledges {
+      pledges.push_back(
+        e.storage()
+          .persistent()
+          .get::<_, Pledge>(&StorageConst::Pledge(round_id, i))
+          .expect("not initialized yet")
+      );
+  }
+  pledges
+}
+
+//The tally of the pairwise weights starts from the first association, with a weight of 0 for each association.
+fn get_pair_tally(e: &Env, round_id: u32, associations: u32) -> PairTally {
+  if let Some(tally) = e.storage().persistent().get::<_, PairTally>(&StorageConst::PairTally(round_id)) {
+      return tally;
+  }
+  let mut weights: Vec<i128> = Vec::new(e);
+  for _ in 0..associations {
+      weights.push_back(0);
+  }
+  PairTally { association: 0, donor: 0, other: 0, weights }
+}
+
+fn get_commit_reveal(e: &Env, round_id: u32) -> Option<CommitRevealConfig> {
+  e.storage()
+      .instance()
+      .get::<_, CommitRevealConfig>(&StorageConst::CommitReveal(round_id))
+}
+
+fn get_committer(e: &Env, round_id: u32, i: u32) -> Address {
+  e.storage()
+    .persistent()
+    .get::<_, Address>(&StorageConst::Committer(round_id, i))
+    .expect("not initialized yet")
+}
+
+fn get_forfeited(e: &Env, round_id: u32) -> i128 {
+  e.storage()
+      .instance()
+      .get::<_, i128>(&StorageConst::Forfeited(round_id))
+      .unwrap_or(0)
+}
+
+fn get_commitment(e: &Env, round_id: u32, donor: &Address) -> Option<Commitment> {
+  e.storage()
+    .persistent()
+    .get::<_, Commitment>(&StorageConst::Commitment(round_id, donor.clone()))
+}
+
+//A round without a token config only accepts its base token.
+fn get_token_config(e: &Env, round_id: u32) -> Option<TokenConfig> {
+  e.storage()
+      .instance()
+      .get::<_, TokenConfig>(&StorageConst::TokenConfig(round_id))
+}
+
+fn get_accepted_tokens(e: &Env, round_id: u32) -> Vec<Address> {
+  match get_token_config(e, round_id) {
+      Some(config) => config.tokens,
+      None => Vec::new(e),
+  }
+}
+
+fn get_association_tokens(e: &Env, round_id: u32, association: &Address) -> Map<Address, TokenDonation> {
+  e.storage()
+      .persistent()
+      .get::<_, Map<Address, TokenDonation>>(&StorageConst::AssociationTokens(round_id, association.clone()))
+      .unwrap_or(Map::new(e))
+}
+
+fn get_ttl_config(e: &Env) -> TtlConfig {
+  e.storage()
+      .instance()
+      .get::<_, TtlConfig>(&StorageConst::TtlConfig)
+      .unwrap_or(TtlConfig { low: BUMP_LOW, high: BUMP_HIGH })
+}
+
+fn get_manager(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&StorageConst::Manager)
+      .expect("not initialized yet")
+}
+
+//The admin is kept by the manager contract, it gets what is left of the matching pool when there is no sponsor.
+fn get_admin_address(e: &Env) -> Address {
+  ManagerClient::new(e, &get_manager(e)).admin()
+}
+
+fn get_total(e: &Env, round_id: u32) -> i128 {
+  e.storage()
+      .instance()
+      .get::<_, i128>(&StorageConst::TotalAmount(round_id))
+      .expect("not initialized yet")
+}
+
+fn get_state(e: &Env, round_id: u32) -> State {
+  e.storage()
+      .instance()
+      .get::<_, State>(&StorageConst::State(round_id))
+      .expect("not initialized yet")
+}
+
+fn set_state(e: &Env, round_id: u32, state: State) {
+  e.storage().instance().set(&StorageConst::State(round_id), &state);
+  e.events().publish((symbol_short!("state"), EVENTS_VERSION, round_id), state);
+}
+
+fn get_amount(e: &Env, round_id: u32) -> i128 {
+  e.storage()
+      .instance()
+      .get::<_, i128>(&StorageConst::TotalAmount(round_id))
+      .expect("not initialized yet")
+}
+
+fn get_distribution_wasm(e: &Env) -> BytesN<32> {
+  e.storage()
+      .instance()
+      .get::<_, BytesN<32>>(&StorageConst::DistributionWasm)
+      .expect("not initialized yet")
+}
+
+fn get_sponsor(e: &Env, round_id: u32, i: u32) -> Address {
+  e.storage()
+    .persistent()
+    .get::<_, Address>(&StorageConst::SponsorIndex(round_id, i))
+    .expect("not initialized yet")
+}
+
+fn get_sponsor_amount(e: &Env, round_id: u32, sponsor: &Address) -> i128 {
+  e.storage()
+    .persistent()
+    .get::<_, i128>(&StorageConst::Sponsor(round_id, sponsor.clone()))
+    .unwrap_or(0)
+}
+
+//The unmatched pool of a round is only recorded by withdraw, until it is all returned.
+fn get_unmatched(e: &Env, round_id: u32) -> Option<UnmatchedPool> {
+  e.storage()
+    .persistent()
+    .get::<_, UnmatchedPool>(&StorageConst::Unmatched(round_id))
+}
+
+fn get_matching_pool(e: &Env, round_id: u32) -> i128 {
+  e.storage()
+      .instance()
+      .get::<_, i128>(&StorageConst::MatchingPool(round_id))
+      .expect("not initialized yet")
+}
+
+/*
+Function to get the maximum matching of a single association for a given matching pool,
+the lowest of the absolute cap and the basis points cap, or None when there is no cap.
+*/
+fn get_matching_cap(e: &Env, round_id: u32, total_funding: i128) -> Result<Option<i128>, Error> {
+  let config: MatchingConfig = get_matching_config(e, round_id);
+  let mut cap: Option<i128> = None;
+  if config.cap > 0 {
+    cap = Some(config.cap);
+  }
+  if config.cap_bps > 0 {
+    let bps_cap: i128 = mul_div(total_funding, config.cap_bps as i128, MAX_BPS)?;
+    cap = Some(cap.map_or(bps_cap, |cap| cap.min(bps_cap)));
+  }
+  Ok(cap)
+}
+
+fn get_precision(e: &Env, round_id: u32) -> u32 {
+  get_matching_config(e, round_id).precision
+}
+
+fn get_matching_config(e: &Env, round_id: u32) -> MatchingConfig {
+  e.storage()
+      .instance()
+      .get::<_, MatchingConfig>(&StorageConst::MatchingConfig(round_id))
+      .expect("not initialized yet")
+}
+
+fn get_matching_mode(e: &Env, round_id: u32) -> MatchingMode {
+  get_matching_config(e, round_id).mode
+}
+
+fn get_pairwise_threshold(e: &Env, round_id: u32) -> i128 {
+  get_matching_config(e, round_id).pairwise_threshold
+}
+
+fn get_scale(e: &Env, round_id: u32) -> i128 {
+  powi(10, get_precision(e, round_id))
+}
+/*
+The interface of the price oracle of a round, any contract implementing it can be plugged in with the set_token_config of the manager contract:
+lastprice: the price of one unit of token in units of base, scaled by 10^decimals, None when it has no price.
+decimals: the number of decimal digits of the prices.
+*/
+#[contractclient(name = "OracleClient")]
+pub trait PriceOracleInterface {
+  fn lastprice(
+    env: Env,
+    base: Address,
+    token: Address,
+  ) -> Option<PriceData>;
+  fn decimals(
+    env: Env,
+  ) -> u32;
+}
+
+/*
+The interface of the DistributionContract (transfer-contract) deployed by withdraw.
+Its Owner, Operator and Treasurer are numbered like the ones of Role, so Role::Treasurer is granted as its treasurer,
+it has no Reviewer and keeps its number (3) unused.
+*/
+#[contractclient(name = "DistributionClient")]
+pub trait DistributionInterface {
+  fn init(
+    env: Env,
+    admin: Address,
+    token_address: Address,
+    associations: Vec<Address>,
+    deadline: u64,
+  );
+
+  fn distribute(
+    env: Env,
+    distributor: Address,
+    associations: Vec<FinalAssociation>,
+  );
+
+  fn grant_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  );
+
+  fn propose_admin(
+    env: Env,
+    admin: Address,
+    new_admin: Address,
+  );
+}
+
+/*
+The interface of the manager contract (manager-contract) that initializes this contract,
+it keeps the roles, the admin and the challenges of the rounds.
+*/
+#[contractclient(name = "ManagerClient")]
+pub trait ManagerInterface {
+  fn admin(
+    env: Env,
+  ) -> Address;
+
+  fn has_role(
+    env: Env,
+    role: Role,
+    address: Address,
+  ) -> bool;
+
+  fn open_challenges(
+    env: Env,
+    round_id: u32,
+  ) -> u32;
+}
+
+#[contract]
+pub struct VotingContract;
 
 pub trait VotingTrait{
   /*
-  Initialize the contract, it needs:
-  admin: admin address for this contract, the only one who can modify.
-  token_address: the token this contract will handle.
-  association: a vector of addresses that will form up the associations.
-  deadline: epoch timestamp 
+  Initialize the contract, it is called by the manager contract from its own init, it needs:
+  manager: the manager contract, which keeps the roles and the admin (see ManagerInterface).
+  */
+  fn init(
+    env: Env,
+    manager: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to create a new funding round, it needs:
+  token_address: the token this round will handle.
+  association: a vector of addresses that will form up the associations.
+  deadline: epoch timestamp 
+  config: the matching parameters (precision, matching mode, pairwise threshold and caps).
+  It returns the id of the new round, every other function is scoped by this id.
+  The round starts in the Setup state.
+  */
+  fn create_round(
+    env: Env,
+    admin: Address,
+    token_address: Address,
+    associations: Vec<Address>,
+    deadline: u64,
+    config: MatchingConfig,
+  ) -> Result<u32, Error>;
+
+  /*
+  This function will be called by a treasurer, once the payout has been started, to deploy the distribution
+  contract of a round, send it the funds and hand it the final associations. It closes the round.
+  The treasurer is granted the treasurer role of the distribution contract and the admin of this contract
+  is proposed as its admin, it has to call accept_admin of the distribution contract to take over.
+  The donations to the suspended associations have to be refunded with refund_association first.
+   */
+  fn withdraw(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone after the withdraw to send back to up to limit sponsors
+  their share of the matching pool the associations did not get.
+  It returns the number of sponsors left to pay, it has to be called again until it returns 0.
+  */
+  fn return_unmatched(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error>;
+
+
+  /*
+  This function will be called by a donor to deposit in the base token of a round.
+  memo: an optional message or reference kept with the donation (see Memo).
+  A private memo is left out of the deposit event, which carries None in its place, and out of the memos query:
+  only its count shows in memo_count, the text stays in the storage of the contract.
   */
-  fn init(
+  fn deposit(
+    env: Env,
+    sender: Address,
+    round_id: u32,
+    association: Address,
+    amount: i64,
+    memo: Option<Memo>,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a donor to deposit in one of the accepted tokens of a round (or its base token),
+  the donation counts for the matching with its value in the base token given by the oracle.
+  A private memo is left out of the dep_token event the same way as in deposit.
+  */
+  fn deposit_token(
+    env: Env,
+    sender: Address,
+    round_id: u32,
+    association: Address,
+    token: Address,
+    amount: i64,
+    memo: Option<Memo>,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone to present the attestation of a donor until the donations are closed,
+  signature: the ed25519 signature of the verifier over the XDR of the ScVal vector [contract, donor, round_id, expiry],
+  contract being the address of this contract. An invalid signature makes the call fail.
+  The donations of the donor only count for the matching if expiry is not before the deadline of the round.
+  */
+  fn attest(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+    expiry: u64,
+    signature: BytesN<64>,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a donor of a commit-reveal round to escrow amount in the base token until the deadline,
+  hash: the hash of the allocations and of a salt (see Commitment). A donor can commit again to add to the amount,
+  the new hash replaces the previous one and covers the allocations of the whole amount.
+  */
+  fn commit(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+    hash: BytesN<32>,
+    amount: i64,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a donor of a commit-reveal round between the deadline and the reveal deadline,
+  the allocations become their contributions to the associations.
+  */
+  fn reveal(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+    allocations: Map<Address, i128>,
+    salt: BytesN<32>,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone after the reveal deadline of a commit-reveal round to settle up to limit (capped to 50)
+  of the commitments not revealed, calculate_funding can only be called once they are all settled. It returns how many are left.
+  */
+  fn settle_commitments(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error>;
+
+  /*
+  This function will be called by an operator to calculate the fundings once the deadline has passed.
+  It can be called again until start_payout to answer the challenges of the review,
+  the round goes back to Calculated and end_funding has to start a new challenge period.
+  In the pairwise mode the pairs of donors have to be tallied first with tally_pairs.
+  */
+  fn calculate_funding(
     env: Env,
     admin: Address,
-    token_address: Address,
-    associations: Vec<Address>,
-    deadline: u64,
-    contract_transfer: Address,
+    round_id: u32,
   ) -> Result<(), Error>;
 
   /*
-  This function will be called by the admin to add a new association to the contract.
-   */
-  fn withdraw(
+  This function will be called by anyone, in the pairwise mode, to tally the weights of up to limit pairs of donors
+  once the donations are over. It returns the number of associations whose pairs are still to tally,
+  it has to be called again until it returns 0. Removing or suspending an association starts the tally over.
+  */
+  fn tally_pairs(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error>;
+
+  /*
+  This function will be called by an operator to freeze the calculated funding and put it in review,
+  the challenge period starts and the unresolved challenges still block start_payout.
+  */
+  fn end_funding(
     env: Env,
     admin: Address,
+    round_id: u32,
   ) -> Result<(), Error>;
 
+  /*
+  This function will be called by an operator to approve the reviewed funding so it can be withdrawn,
+  once the challenge period is over and every challenge is resolved.
+  */
+  fn start_payout(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>;
 
-  fn deposit(
+  // This function will be called by an operator to add a new association to a round.
+  fn add_association(
     env: Env,
+    admin: Address,
+    round_id: u32,
     association: Address,
-    sender: Address,
-    amount: i64,
   ) -> Result<(), Error>;
 
-  /*
-  This function will be called by the admin to calculate the fundings.
-  */
-  fn calculate_funding(
+  /*
+  This function will be called by an operator to remove an association added by mistake,
+  every donation made to it is then refunded to its donor with refund_association.
+  */
+  fn remove_association(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone to refund up to limit donors of a removed association,
+  or of a suspended association once the payout has started. It returns the number of donors left to refund,
+  it has to be called again until it returns 0.
+  */
+  fn refund_association(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    limit: u32,
+  ) -> Result<u32, Error>;
+
+  /*
+  This function will be called by an operator to suspend (or resume with suspended = false) an association.
+  A suspended association does not accept deposits, its donations are excluded from the matching
+  and refunded to their donors with refund_association before the withdraw, it is flagged as suspended in the final associations so the payouts skip it.
+  */
+  fn suspend_association(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    association: Address,
+    suspended: bool,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by the manager contract to store the settings of a round once it has checked them (see RoundConfig in the shared crate),
+  the operators change them with its set_verifier, set_contribution_limits, set_commit_reveal, set_token_config and set_challenge_period.
+  */
+  fn configure(
+    env: Env,
+    round_id: u32,
+    verifier: Option<BytesN<32>>,
+    limits: Option<ContributionLimits>,
+    commit_reveal: Option<CommitRevealConfig>,
+    tokens: Option<TokenConfig>,
+    challenge_period: u64,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by the manager contract when a reviewer upholds a challenge,
+  the calculated funding is discarded and the round goes back to Ended to be calculated again.
+  */
+  fn reset_funding(
+    env: Env,
+    round_id: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an owner to set the hash of the uploaded transfer-contract wasm,
+  withdraw deploys a new distribution contract from it for every round.
+  */
+  fn set_distribution_wasm(
+    env: Env,
+    admin: Address,
+    wasm_hash: BytesN<32>,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an owner to set the thresholds used to extend the time to live of the entries.
+  fn set_ttl_config(
+    env: Env,
+    admin: Address,
+    config: TtlConfig,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone to extend the time to live of the contract instance and of the persistent entries
+  of a round, for up to limit associations starting at offset (capped to 50), call it for every page to bump the whole round.
+  The donors and memos of each association are bumped with extend_association_ttl.
+  */
+  fn extend_ttl(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone to extend the time to live of up to limit donors of an association starting at offset
+  (capped to 50), with their donations, and of its memos at the same positions. A removed association can be bumped until it is refunded.
+  */
+  fn extend_association_ttl(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to let the associations apply to a round until application_deadline,
+  which can not be after the deadline of the round.
+  */
+  fn open_applications(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    application_deadline: u64,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to close the setup (or the applications) of a round
+  and let the donors deposit until the deadline.
+  */
+  fn open_donations(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a sponsor to pledge to match the direct donations of a round (see Pledge),
+  the budget is escrowed and what the matches do not use is returned on withdraw.
+  */
+  fn pledge(
+    env: Env,
+    sponsor: Address,
+    round_id: u32,
+    multiplier_bps: u32,
+    budget: i128,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a sponsor to add funds to the matching pool of a round.
+  The matching pool is distributed quadratically on top of the direct donations.
+  */
+  fn fund_matching_pool(
+    env: Env,
+    sponsor: Address,
+    round_id: u32,
+    amount: i128,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to abort a round before its funds are withdrawn.
+  A cancelled round does not accept any other call than refund.
+  */
+  fn cancel_round(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a donor or a sponsor of a cancelled round to get back
+  everything they deposited in it.
+  */
+  fn refund(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone, like the manager and query contracts, to read an entry of the storage:
+  key: a StorageConst, the persistent entry is returned when there is one, otherwise the instance one, None when neither exists.
+  */
+  fn entry(
+    env: Env,
+    key: Val,
+  ) -> Option<Val>;
+}
+
+#[contractimpl]
+impl VotingTrait for VotingContract {
+  fn init(
+    env: Env,
+    manager: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    manager.require_auth();
+    if env.storage().instance().has(&StorageConst::Manager) {
+      log!(
+        &env,
+        "Something went wrong, the contract is already initizalized."
+      );
+      return Err(Error::AlreadyInitialized);
+    }
+    let round_count: u32 = 0;
+    env.storage().instance().set(&StorageConst::Manager, &manager);
+    env.storage().instance().set(&StorageConst::RoundCount, &round_count);
+    env.events().publish((symbol_short!("init"), EVENTS_VERSION), manager);
+    Ok(())
+  }
+
+  fn create_round(
+    env: Env,
+    admin: Address,
+    token_address: Address,
+    associations: Vec<Address>,
+    deadline: u64,
+    config: MatchingConfig,
+  ) -> Result<u32, Error> {
+    bump_instance(&env);
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    let act_ledger = get_ledger_timestamp(&env);
+    if deadline < act_ledger{
+      log!(
+        &env,
+        "Something went wrong, the deadline is before the current deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    if associations.is_empty() {
+      log!(
+        &env,
+        "Something went wrong, the length of the associations is less than 1."
+      );
+      return Err(Error::InvalidAssociation);
+    }
+    if config.precision == 0 || config.precision > MAX_PRECISION {
+      log!(
+        &env,
+        "Something went wrong, the precision is 0 or bigger than the maximum allowed."
+      );
+      return Err(Error::InvalidPrecision);
+    }
+    if config.mode == MatchingMode::Pairwise && config.pairwise_threshold <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the pairwise threshold is 0 or less than 0."
+      );
+      return Err(Error::InvalidAmount);
+    }
+    if config.cap < 0 || config.cap_bps as i128 > MAX_BPS {
+      log!(
+        &env,
+        "Something went wrong, the matching cap is less than 0 or bigger than the matching pool."
+      );
+      return Err(Error::InvalidAmount);
+    }
+
+    let round_id: u32 = get_round_count(&env);
+    let total_amount: i128 = 0;
+    env.storage().instance().set(&StorageConst::AssetAdress(round_id), &token_address);
+    env.storage().instance().set(&StorageConst::Deadline(round_id), &deadline);
+    init_associations(&env, round_id, &associations)?;
+    env.storage().instance().set(&StorageConst::TotalAmount(round_id), &total_amount);
+    env.storage().instance().set(&StorageConst::MatchingPool(round_id), &total_amount);
+    env.storage().instance().set(&StorageConst::MatchingConfig(round_id), &config);
+    env.storage().instance().set(&StorageConst::ChallengePeriod(round_id), &DEFAULT_CHALLENGE_PERIOD);
+    env.storage().instance().set(&StorageConst::RoundCount, &(round_id + 1));
+    env.events().publish((symbol_short!("round"), EVENTS_VERSION, round_id), (token_address, deadline, config));
+    for association in associations.iter() {
+      env.events().publish((symbol_short!("assoc_add"), EVENTS_VERSION, round_id), association);
+    }
+    set_state(&env, round_id, State::Setup);
+    Ok(round_id)
+  }
+  
+  fn withdraw(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Treasurer)?;
+    if get_state(&env, round_id) == State::Closed {
+      log!(
+        &env,
+        "Something went wrong, the recipients have already claimed their funds."
+      );
+      return Err(Error::AlreadyWithdrawn);
+    }
+    check_state(&env, round_id, &[State::Payout])?;
+    if !env.storage().instance().has(&StorageConst::DistributionWasm) {
+      log!(
+        &env,
+        "Something went wrong, the wasm of the distribution contract has not been set."
+      );
+      return Err(Error::DistributionNotSet);
+    }
+    withdraw(&env, round_id, &admin)?;
+    set_state(&env, round_id, State::Closed);
+    Ok(())
+  }
+
+  fn return_unmatched(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Closed])?;
+    return_unmatched(&env, round_id, limit)
+  }
+
+  fn calculate_funding(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Donations, State::Ended, State::Calculated, State::Review])?;
+    check_donations_closed(&env, round_id)?;
+    let index: Vec<Address> = get_association_index(&env, round_id);
+    let mut totals: Vec<AssociationTotals> = Vec::new(&env);
+    for name in index.iter() {
+      totals.push_back(get_association_totals(&env, round_id, &name));
+    }
+    let mut mutable_final_assoc: Vec<FinalAssociation> = calculate_funding(&env, round_id, &index, &totals)?;
+    apply_pledges(&env, round_id, &mut mutable_final_assoc, &totals)?;
+    set_final_associations(&env, round_id, &mutable_final_assoc);
+    env.events().publish((symbol_short!("calculate"), EVENTS_VERSION, round_id), (get_amount(&env, round_id), get_matching_pool(&env, round_id)));
+    set_state(&env, round_id, State::Calculated);
+    Ok(())
+  }
+
+  fn tally_pairs(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Donations, State::Ended, State::Calculated, State::Review])?;
+    if get_matching_mode(&env, round_id) != MatchingMode::Pairwise {
+      log!(
+        &env,
+        "Something went wrong, the round does not use the pairwise matching."
+      );
+      return Err(Error::InvalidState);
+    }
+    check_donations_closed(&env, round_id)?;
+    tally_pairs(&env, round_id, limit)
+  }
+
+  fn end_funding(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Calculated])?;
+    let final_amount = get_amount(&env, round_id);
+    if final_amount < 0 {
+      log!(
+        &env,
+        "Something went wrong, the final amount is less than 0."
+      );
+      return Err(Error::InvalidAmount);
+    }
+    let review_deadline: u64 = get_ledger_timestamp(&env).checked_add(get_challenge_period(&env, round_id)).ok_or(Error::Overflow)?;
+    env.storage().instance().set(&StorageConst::ReviewDeadline(round_id), &review_deadline);
+    set_state(&env, round_id, State::Review);
+    Ok(())
+  }
+
+  fn start_payout(
     env: Env,
     admin: Address,
-  ) -> Result<(), Error>;
+    round_id: u32,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Review])?;
+    if get_ledger_timestamp(&env) < get_review_deadline(&env, round_id) {
+      log!(
+        &env,
+        "Something went wrong, the challenge period is not over."
+      );
+      return Err(Error::ChallengePeriod);
+    }
+    if has_unresolved_challenges(&env, round_id) {
+      log!(
+        &env,
+        "Something went wrong, the funding has unresolved challenges."
+      );
+      return Err(Error::UnresolvedChallenge);
+    }
+    set_state(&env, round_id, State::Payout);
+    Ok(())
+  }
 
-  fn end_funding(
+  fn add_association(
     env: Env,
     admin: Address,
-  ) -> Result<(), Error>;
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations])?;
+    let deadline = get_deadline(&env, round_id);
+    if deadline < get_ledger_timestamp(&env){
+      log!(
+        &env,
+        "Something went wrong, the deadline is before the current deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    add_new_association(&env, round_id, &association)?;
+    env.events().publish((symbol_short!("assoc_add"), EVENTS_VERSION, round_id), association);
+    Ok(())
+  }
 
-  // This function will be called by the admin to add a new association to the contract.
-  fn add_association(
+  fn remove_association(
     env: Env,
     admin: Address,
+    round_id: u32,
     association: Address,
-  ) -> Result<(), Error>;
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations, State::Ended, State::Calculated, State::Review])?;
+    let donors: u32 = remove_association(&env, round_id, &association)?;
+    env.storage().persistent().remove(&StorageConst::PairTally(round_id));
+    reset_final_associations(&env, round_id);
+    env.events().publish((symbol_short!("assoc_rm"), EVENTS_VERSION, round_id), (association, donors));
+    Ok(())
+  }
 
-  fn duplicate_quantities(
+  fn refund_association(
     env: Env,
-    sender: Address,
-  ) -> Result<(), Error>;
-  //This function will be called by anyone to get the associations addresses.
-  fn associations_addresses(
-    env: Env
-  ) -> Vec<Address>;
-  
-  //This function will be called by anyone to get the state of the contract.
-  fn state(
-    env: Env
-  ) -> u32;
-
-  //This function will be called by anyone to get the deadline of the contract.
-  fn deadline(
-    env: Env
-  ) -> u64;
-
-  //This function will be called by anyone to get the amounts per associations.
-  fn associations_amounts(
-    env: Env
-  ) -> Vec<Association>;
+    round_id: u32,
+    association: Address,
+    limit: u32,
+  ) -> Result<u32, Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    let removed: bool = !is_association(&env, round_id, &association)
+      && env.storage().persistent().has(&StorageConst::DonorCount(round_id, association.clone()));
+    let suspended: bool = is_suspended(&env, round_id, &association) && get_state(&env, round_id) == State::Payout;
+    if !removed && !suspended {
+      log!(
+        &env,
+        "Something went wrong, the association is neither removed nor suspended in the payout."
+      );
+      return Err(Error::InvalidAssociation);
+    }
+    Ok(refund_donors(&env, round_id, &association, limit))
+  }
 
-  //This function will be calle by anyone to get the amount of a certain association.
-  fn association_amount(
+  fn suspend_association(
     env: Env,
+    admin: Address,
+    round_id: u32,
     association: Address,
-  ) -> Vec<i64>;
+    suspended: bool,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations, State::Ended, State::Calculated, State::Review])?;
+    set_association_suspended(&env, round_id, &association, suspended)?;
+    env.storage().persistent().remove(&StorageConst::PairTally(round_id));
+    reset_final_associations(&env, round_id);
+    env.events().publish((symbol_short!("suspend"), EVENTS_VERSION, round_id), (association, suspended));
+    Ok(())
+  }
 
-  fn total_amount(
-    env: Env
-  ) -> i128;
-  
-  fn total_final_associations(
-    env: Env
-  ) -> Vec<FinalAssociation>;
-}
+  fn configure(
+    env: Env,
+    round_id: u32,
+    verifier: Option<BytesN<32>>,
+    limits: Option<ContributionLimits>,
+    commit_reveal: Option<CommitRevealConfig>,
+    tokens: Option<TokenConfig>,
+    challenge_period: u64,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    get_manager(&env).require_auth();
+    set_optional(&env, &StorageConst::Verifier(round_id), verifier);
+    set_optional(&env, &StorageConst::ContributionLimits(round_id), limits);
+    set_optional(&env, &StorageConst::CommitReveal(round_id), commit_reveal);
+    set_optional(&env, &StorageConst::TokenConfig(round_id), tokens);
+    env.storage().instance().set(&StorageConst::ChallengePeriod(round_id), &challenge_period);
+    Ok(())
+  }
 
-#[contractimpl]
-impl VotingTrait for VotingContract {
-  fn init(
+  fn reset_funding(
     env: Env,
-    admin: Address,
-    token_address: Address,
-    associations: Vec<Address>,
-    deadline: u64,
-    contract_transfer: Address,
+    round_id: u32,
   ) -> Result<(), Error> {
-    admin.require_auth();
-    if env.storage().instance().has(&StorageConst::AdminAddress) {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    get_manager(&env).require_auth();
+    reset_final_associations(&env, round_id);
+    Ok(())
+  }
+
+  fn deposit(
+    env: Env,
+    sender: Address,
+    round_id: u32,
+    association: Address,
+    amount: i64,
+    memo: Option<Memo>,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Donations])?;
+    if get_commit_reveal(&env, round_id).is_some() {
       log!(
         &env,
-        "Something went wrong, the contract is already initizalized."
+        "Something went wrong, the round only accepts commitments."
       );
-      return Err(Error::AlreadyInitialized);
+      return Err(Error::InvalidState);
     }
-    let act_ledger = get_ledger_timestamp(&env);
-    if deadline < act_ledger{
+    let deadline = get_deadline(&env, round_id);
+    if deadline < get_ledger_timestamp(&env){
       log!(
         &env,
         "Something went wrong, the deadline is before the current deadline."
       );
       return Err(Error::InvalidTimestamp);
     }
-    if associations.len() < 1 {
+    if amount <= 0 {
       log!(
         &env,
-        "Something went wrong, the length of the associations is less than 1."
+        "Something went wrong, the amount is 0 or less than 0."
       );
-      return Err(Error::InvalidAssociation);
+      return Err(Error::InvalidAmount);
     }
-
-    let total_amount: i128 = 0;
-    let associations_vec: Vec<Association> = init_associations(&env, associations);
-    env.storage().instance().set(&StorageConst::AdminAddress, &admin);
-    env.storage().instance().set(&StorageConst::AssetAdress, &token_address);
-    env.storage().instance().set(&StorageConst::Deadline, &deadline);
-    env.storage().instance().set(&StorageConst::Associations, &associations_vec);
-    env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
-    env.storage().instance().set(&StorageConst::ContractCallAddress, &contract_transfer);
+    sender.require_auth();
+    let mut total_amount: i128 = get_total(&env, round_id);
+    total_amount += amount as i128;
+    //add to storage
+    add_contribution(&env, round_id, &association, &sender, amount as i128)?;
+    let public_memo: Option<Memo> = add_memo(&env, round_id, &association, &sender, &memo)?;
+    transfer(&env, round_id, &sender, &env.current_contract_address(), &(amount as i128));
+    env.storage().instance().set(&StorageConst::TotalAmount(round_id), &total_amount);
+    env.events().publish((symbol_short!("deposit"), EVENTS_VERSION, round_id), (sender, association, amount, public_memo));
     Ok(())
   }
-  
-  fn withdraw(
+
+  fn deposit_token(
     env: Env,
-    admin: Address,
-  ) -> Result<(), Error> {
-    admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
+    sender: Address,
+    round_id: u32,
+    association: Address,
+    token: Address,
+    amount: i64,
+    memo: Option<Memo>,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    if token == get_token_address(&env, round_id) {
+      return Self::deposit(env, sender, round_id, association, amount, memo);
+    }
+    check_state(&env, round_id, &[State::Donations])?;
+    if get_commit_reveal(&env, round_id).is_some() {
       log!(
         &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
+        "Something went wrong, the round only accepts commitments."
       );
-      return Err(Error::InvalidAuth);
+      return Err(Error::InvalidState);
     }
-    let recipients_claimed: bool = get_recipients_claimed(&env);
-    if recipients_claimed{
+    let deadline = get_deadline(&env, round_id);
+    if deadline < get_ledger_timestamp(&env){
       log!(
         &env,
-        "Something went wrong, the recipients have already claimed their funds."
+        "Something went wrong, the deadline is before the current deadline."
       );
-      return Err(Error::AlreadyWithdrawn);
+      return Err(Error::InvalidTimestamp);
+    }
+    if amount <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the amount is 0 or less than 0."
+      );
+      return Err(Error::InvalidAmount);
     }
-    withdraw(&env);
-    env.storage().instance().set(&StorageConst::RecipientsClaimed, &true);
+    sender.require_auth();
+    let value: i128 = get_token_value(&env, round_id, &token, amount as i128)?;
+    add_contribution(&env, round_id, &association, &sender, value)?;
+    add_token_contribution(&env, round_id, &association, &sender, &token, &TokenDonation{amount: amount as i128, value});
+    let public_memo: Option<Memo> = add_memo(&env, round_id, &association, &sender, &memo)?;
+    token::Client::new(&env, &token).transfer(&sender, &env.current_contract_address(), &(amount as i128));
+    env.events().publish((symbol_short!("dep_token"), EVENTS_VERSION, round_id), (sender, association, token, amount, value, public_memo));
     Ok(())
   }
 
-  fn calculate_funding(
+  fn attest(
     env: Env,
-    admin: Address,
-  ) -> Result<(), Error> {
-    admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
+    donor: Address,
+    round_id: u32,
+    expiry: u64,
+    signature: BytesN<64>,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations])?;
+    let verifier: BytesN<32> = match get_verifier(&env, round_id) {
+      Some(verifier) => verifier,
+      None => {
+        log!(
+          &env,
+          "Something went wrong, the round has no verifier."
+        );
+        return Err(Error::InvalidAttestation);
+      }
+    };
+    if expiry < get_ledger_timestamp(&env) {
       log!(
         &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
+        "Something went wrong, the attestation has expired."
       );
-      return Err(Error::InvalidAuth);
+      return Err(Error::InvalidAttestation);
+    }
+    let message: Bytes = (env.current_contract_address(), donor.clone(), round_id, expiry).to_xdr(&env);
+    env.crypto().ed25519_verify(&verifier, &message, &signature);
+    let was_attested: bool = is_attested(&env, round_id, &donor);
+    set_persistent(&env, &StorageConst::Attested(round_id, donor.clone()), &expiry);
+    if is_attested(&env, round_id, &donor) != was_attested {
+      recount_donor(&env, round_id, &donor, !was_attested)?;
+      env.storage().persistent().remove(&StorageConst::PairTally(round_id));
     }
-    let mut mutable_assoc: Vec<Association> = get_associations(&env);
-    let mutable_final_assoc: Vec<FinalAssociation> = calculate_funding(&env, &mut mutable_assoc);
-    env.storage().instance().set(&StorageConst::FinalAssociations, &mutable_final_assoc);
+    env.events().publish((symbol_short!("attested"), EVENTS_VERSION, round_id), (donor, expiry));
     Ok(())
   }
 
-  fn end_funding(
+  fn commit(
     env: Env,
-    admin: Address,
+    donor: Address,
+    round_id: u32,
+    hash: BytesN<32>,
+    amount: i64,
   ) -> Result<(), Error>{
-    admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Donations])?;
+    if get_commit_reveal(&env, round_id).is_none() {
       log!(
         &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
+        "Something went wrong, the round is not in the commit-reveal mode."
       );
-      return Err(Error::InvalidAuth);
+      return Err(Error::InvalidState);
     }
-    let final_amount = get_amount(&env);
-    if final_amount < 0 {
+    if get_deadline(&env, round_id) < get_ledger_timestamp(&env) {
       log!(
         &env,
-        "Something went wrong, the final amount is less than 0."
+        "Something went wrong, the deadline is before the current deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    if amount <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the amount is 0 or less than 0."
       );
       return Err(Error::InvalidAmount);
     }
-    let final_assoc = get_final_associations(&env);
-    if final_assoc.len() > 0 {
-      let deadline: u64 = env.ledger().timestamp();
-      env.storage().instance().set(&StorageConst::Deadline, &deadline);
-      env.storage().instance().set(&StorageConst::RecipientsClaimed, &false);
+    let committed: i128 = get_commitment(&env, round_id, &donor).map_or(0, |commitment| commitment.amount);
+    if let Some(limits) = get_contribution_limits(&env, round_id) {
+      if (amount as i128) < limits.min_deposit || (limits.max_per_donor > 0 && committed + amount as i128 > limits.max_per_donor) {
+        log!(
+          &env,
+          "Something went wrong, the amount is out of the contribution limits."
+        );
+        return Err(Error::InvalidContribution);
+      }
     }
-   
+    donor.require_auth();
+    let commitment: Commitment = add_commitment(&env, round_id, &donor, &hash, amount as i128);
+    transfer(&env, round_id, &donor, &env.current_contract_address(), &(amount as i128));
+    env.events().publish((symbol_short!("commit"), EVENTS_VERSION, round_id), (donor, commitment.amount));
     Ok(())
   }
 
-  fn add_association(
+  fn reveal(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+    allocations: Map<Address, i128>,
+    salt: BytesN<32>,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Donations])?;
+    let config: CommitRevealConfig = match get_commit_reveal(&env, round_id) {
+      Some(config) => config,
+      None => {
+        log!(
+          &env,
+          "Something went wrong, the round is not in the commit-reveal mode."
+        );
+        return Err(Error::InvalidState);
+      }
+    };
+    let now: u64 = get_ledger_timestamp(&env);
+    if now <= get_deadline(&env, round_id) || now > config.reveal_deadline {
+      log!(
+        &env,
+        "Something went wrong, the commitments can only be revealed between the deadline and the reveal deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    donor.require_auth();
+    let (revealed, returned): (i128, i128) = reveal_commitment(&env, round_id, &donor, &allocations, &salt)?;
+    let total_amount: i128 = get_total(&env, round_id) + revealed;
+    env.storage().instance().set(&StorageConst::TotalAmount(round_id), &total_amount);
+    env.events().publish((symbol_short!("reveal"), EVENTS_VERSION, round_id), (donor, allocations, returned));
+    Ok(())
+  }
+
+  fn settle_commitments(
+    env: Env,
+    round_id: u32,
+    limit: u32,
+  ) -> Result<u32, Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Donations])?;
+    let config: CommitRevealConfig = match get_commit_reveal(&env, round_id) {
+      Some(config) => config,
+      None => {
+        log!(
+          &env,
+          "Something went wrong, the round is not in the commit-reveal mode."
+        );
+        return Err(Error::InvalidState);
+      }
+    };
+    if get_ledger_timestamp(&env) <= config.reveal_deadline {
+      log!(
+        &env,
+        "Something went wrong, the commitments can still be revealed."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    Ok(settle_commitments(&env, round_id, &config, limit))
+  }
+
+  fn set_distribution_wasm(
     env: Env,
     admin: Address,
-    association: Address,
+    wasm_hash: BytesN<32>,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    admin.require_auth();
+    check_role(&env, &admin, Role::Owner)?;
+    env.storage().instance().set(&StorageConst::DistributionWasm, &wasm_hash);
+    env.events().publish((symbol_short!("dist_wasm"), EVENTS_VERSION), wasm_hash);
+    Ok(())
+  }
+
+  fn set_ttl_config(
+    env: Env,
+    admin: Address,
+    config: TtlConfig,
   ) -> Result<(), Error> {
     admin.require_auth();
-    let admin_address = get_admin_address(&env);
-    if admin_address != admin {
+    check_role(&env, &admin, Role::Owner)?;
+    if config.low == 0 || config.low > config.high || config.high > MAX_TTL {
       log!(
         &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
+        "Something went wrong, the low threshold is 0 or bigger than the high one, or the high one is bigger than the maximum allowed."
       );
-      return Err(Error::InvalidAuth);
+      return Err(Error::InvalidTtl);
     }
-    let deadline = get_deadline(&env);
-    if deadline < get_ledger_timestamp(&env){
+    env.storage().instance().set(&StorageConst::TtlConfig, &config);
+    bump_instance(&env);
+    env.events().publish((symbol_short!("ttl"), EVENTS_VERSION), config);
+    Ok(())
+  }
+
+  fn extend_ttl(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round_exists(&env, round_id)?;
+    bump_round(&env, round_id, offset, limit);
+    Ok(())
+  }
+
+  fn extend_association_ttl(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round_exists(&env, round_id)?;
+    bump_association(&env, round_id, &association, offset, limit);
+    Ok(())
+  }
+
+  fn open_applications(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    application_deadline: u64,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications])?;
+    if application_deadline < get_ledger_timestamp(&env) || application_deadline > get_deadline(&env, round_id) {
       log!(
         &env,
-        "Something went wrong, the deadline is before the current deadline."
+        "Something went wrong, the application deadline is not between now and the deadline of the round."
       );
       return Err(Error::InvalidTimestamp);
     }
-    let mut mutable_assoc: Vec<Association> = get_associations(&env);
-    add_new_association(&env, &mut mutable_assoc, &association);
-    env.storage().instance().set(&StorageConst::Associations, &mutable_assoc);
+    env.storage().instance().set(&StorageConst::ApplicationDeadline(round_id), &application_deadline);
+    env.events().publish((symbol_short!("apps_open"), EVENTS_VERSION, round_id), application_deadline);
+    set_state(&env, round_id, State::Applications);
     Ok(())
   }
 
-  fn deposit(
+  fn open_donations(
     env: Env,
-    sender: Address,
-    association: Address,
-    amount: i64,
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications])?;
+    set_state(&env, round_id, State::Donations);
+    Ok(())
+  }
+
+
+  fn fund_matching_pool(
+    env: Env,
+    sponsor: Address,
+    round_id: u32,
+    amount: i128,
   ) -> Result<(), Error>{
-    let deadline = get_deadline(&env);
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations])?;
+    let deadline = get_deadline(&env, round_id);
     if deadline < get_ledger_timestamp(&env){
       log!(
         &env,
@@ -570,76 +2727,140 @@ impl VotingTrait for VotingContract {
       );
       return Err(Error::InvalidAmount);
     }
-    sender.require_auth();
-    let mut mutable_assoc: Vec<Association> = get_associations(&env);
-    let mut total_amount: i128 = get_total(&env);
-    total_amount += amount as i128;
-    //add to storage
-    let new_contributions:Vec<Association> = add_contribution(&env ,&mut mutable_assoc, &association, &sender,amount);
-    env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
-    env.storage().instance().set(&StorageConst::Associations, &new_contributions);
+    sponsor.require_auth();
+    let mut matching_pool: i128 = get_matching_pool(&env, round_id);
+    matching_pool = matching_pool.checked_add(amount).ok_or(Error::Overflow)?;
+    transfer(&env, round_id, &sponsor, &env.current_contract_address(), &amount);
+    add_sponsor_amount(&env, round_id, &sponsor, amount);
+    env.storage().instance().set(&StorageConst::MatchingPool(round_id), &matching_pool);
+    env.events().publish((symbol_short!("matching"), EVENTS_VERSION, round_id), (sponsor, amount));
     Ok(())
   }
-  //add a function to get the total amount.
 
-  fn duplicate_quantities(
+  fn pledge(
     env: Env,
-    sender: Address,
+    sponsor: Address,
+    round_id: u32,
+    multiplier_bps: u32,
+    budget: i128,
   ) -> Result<(), Error>{
-    sender.require_auth();
-    let mut final_assocs = get_final_associations(&env);
-    let assoc_duplicate = duplicate(&env, &mut final_assocs, &sender);
-    env.storage().instance().set(&StorageConst::FinalAssociations, &assoc_duplicate);
-
-    let mut amount = get_amount(&env);
-    amount = amount * 2;
-    env.storage().instance().set(&StorageConst::TotalAmount, &amount);
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    check_state(&env, round_id, &[State::Setup, State::Applications, State::Donations])?;
+    let deadline = get_deadline(&env, round_id);
+    if deadline < get_ledger_timestamp(&env){
+      log!(
+        &env,
+        "Something went wrong, the deadline is before the current deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    if multiplier_bps == 0 || budget <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the multiplier or the budget is 0 or less than 0."
+      );
+      return Err(Error::InvalidPledge);
+    }
+    let mut counts: RoundCounts = get_round_counts(&env, round_id);
+    if counts.pledges >= MAX_PLEDGES {
+      log!(
+        &env,
+        "Something went wrong, the round has the maximum number of pledges."
+      );
+      return Err(Error::InvalidPledge);
+    }
+    for pledge in get_pledges(&env, round_id).iter() {
+      if pledge.sponsor == sponsor {
+        log!(
+          &env,
+          "Something went wrong, the sponsor has already pledged to the round."
+        );
+        return Err(Error::InvalidPledge);
+      }
+    }
+    sponsor.require_auth();
+    transfer(&env, round_id, &sponsor, &env.current_contract_address(), &budget);
+    set_persistent(&env, &StorageConst::Pledge(round_id, counts.pledges), &Pledge{sponsor: sponsor.clone(), multiplier_bps, budget, matched: 0});
+    counts.pledges += 1;
+    set_persistent(&env, &StorageConst::Counts(round_id), &counts);
+    env.events().publish((symbol_short!("pledge"), EVENTS_VERSION, round_id), (sponsor, multiplier_bps, budget));
     Ok(())
   }
-  fn associations_addresses(
-    env: Env
-  ) -> Vec<Address> {
-    let associations: Vec<Association> = get_associations(&env);
-    let addresses: Vec<Address> = get_associations_address(&env, associations);
-    addresses
-  }
 
-  fn state(
-    env: Env
-  ) -> u32 {
-    get_state(&env) as u32
-  }
-
-  fn deadline(
-    env: Env
-  ) -> u64 {
-    get_deadline(&env)
-  }
-
-  fn associations_amounts(
-    env: Env
-  ) -> Vec<Association>{
-    get_associations(&env)
-  }
-
-  fn association_amount(
+  fn cancel_round(
     env: Env,
-    association: Address,
-  ) -> Vec<i64> {
-    let associations: Vec<Association> = get_associations(&env);
-    let assoc_amount: Vec<i64> = get_amount_per_association(&env, &associations, &association);
-    assoc_amount
+    admin: Address,
+    round_id: u32,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round(&env, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    if get_state(&env, round_id) == State::Closed {
+      log!(
+        &env,
+        "Something went wrong, the funds of the round have already been withdrawn."
+      );
+      return Err(Error::AlreadyWithdrawn);
+    }
+    set_state(&env, round_id, State::Cancelled);
+    Ok(())
   }
 
-  fn total_amount(
-    env: Env
-  ) -> i128 {
-    get_amount(&env)
+  fn refund(
+    env: Env,
+    donor: Address,
+    round_id: u32,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    check_round_exists(&env, round_id)?;
+    if !is_cancelled(&env, round_id) {
+      log!(
+        &env,
+        "Something went wrong, only the deposits of a cancelled round can be refunded."
+      );
+      return Err(Error::InvalidRound);
+    }
+    donor.require_auth();
+    let (removed, donated): (i128, i128) = remove_contributions(&env, round_id, &donor);
+    let (committed, forfeited): (i128, i128) = match get_commitment(&env, round_id, &donor) {
+      Some(commitment) if commitment.position < get_round_counts(&env, round_id).settled => (commitment.amount, commitment.amount),
+      Some(commitment) => (commitment.amount, 0),
+      None => (0, 0),
+    };
+    let sponsored: i128 = get_sponsor_amount(&env, round_id, &donor);
+    let pledged: i128 = remove_pledge(&env, round_id, &donor);
+    if removed + committed + sponsored + pledged == 0 {
+      log!(
+        &env,
+        "Something went wrong, there is nothing to refund."
+      );
+      return Err(Error::InvalidAmount);
+    }
+    if sponsored > 0 {
+      set_persistent(&env, &StorageConst::Sponsor(round_id, donor.clone()), &0_i128);
+    }
+    env.storage().persistent().remove(&StorageConst::Commitment(round_id, donor.clone()));
+    let total_amount: i128 = get_total(&env, round_id) - donated;
+    let matching_pool: i128 = get_matching_pool(&env, round_id) - sponsored - forfeited;
+    let refunded: i128 = donated + committed + sponsored + pledged;
+    transfer(&env, round_id, &env.current_contract_address(), &donor, &refunded);
+    env.storage().instance().set(&StorageConst::TotalAmount(round_id), &total_amount);
+    env.storage().instance().set(&StorageConst::MatchingPool(round_id), &matching_pool);
+    env.events().publish((symbol_short!("refund"), EVENTS_VERSION, round_id), (donor, refunded));
+    Ok(())
   }
 
-  fn total_final_associations(
-    env: Env
-  ) -> Vec<FinalAssociation> {
-    get_final_associations(&env)
+  fn entry(
+    env: Env,
+    key: Val,
+  ) -> Option<Val> {
+    if let Some(value) = env.storage().persistent().get::<_, Val>(&key) {
+      return Some(value);
+    }
+    env.storage().instance().get::<_, Val>(&key)
   }
 }
+
+mod test;
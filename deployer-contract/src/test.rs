@@ -0,0 +1,1025 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+  testutils::{Address as _, Events, Ledger},
+  token, Address, Env, IntoVal, String,
+};
+
+/*
+The voting contract is run with the manager-contract and the query-contract, the withdraw deploys the distribution contract
+from the transfer-contract wasm and the donations in other tokens are valued by the mock-oracle-contract,
+build them first with soroban contract build (or cargo build --target wasm32-unknown-unknown --release).
+*/
+mod manager {
+  soroban_sdk::contractimport!(
+    file = "../target/wasm32-unknown-unknown/release/manager_contract.wasm"
+  );
+}
+
+mod query {
+  soroban_sdk::contractimport!(
+    file = "../target/wasm32-unknown-unknown/release/query_contract.wasm"
+  );
+}
+
+mod distribution {
+  soroban_sdk::contractimport!(
+    file = "../target/wasm32-unknown-unknown/release/transfer_contract.wasm"
+  );
+}
+
+mod oracle {
+  soroban_sdk::contractimport!(
+    file = "../target/wasm32-unknown-unknown/release/mock_oracle_contract.wasm"
+  );
+}
+
+const DEADLINE: u64 = 1000;
+
+struct Setup<'a> {
+  env: Env,
+  admin: Address,
+  token: token::Client<'a>,
+  token_admin: token::StellarAssetClient<'a>,
+  contract: VotingContractClient<'a>,
+  manager: manager::Client<'a>,
+  query: query::Client<'a>,
+}
+
+impl Setup<'_> {
+  fn new() -> Self {
+    let env: Env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let admin: Address = Address::random(&env);
+    let token_address: Address = env.register_stellar_asset_contract(admin.clone());
+    let contract_id: Address = env.register_contract(None, VotingContract);
+    let contract = VotingContractClient::new(&env, &contract_id);
+    let manager = manager::Client::new(&env, &env.register_contract_wasm(None, manager::WASM));
+    manager.init(&admin, &contract_id);
+    let query = query::Client::new(&env, &env.register_contract_wasm(None, query::WASM));
+    query.init(&contract_id);
+    Setup {
+      token: token::Client::new(&env, &token_address),
+      token_admin: token::StellarAssetClient::new(&env, &token_address),
+      contract,
+      manager,
+      query,
+      admin,
+      env,
+    }
+  }
+
+  fn user(&self, amount: i128) -> Address {
+    let user: Address = Address::random(&self.env);
+    if amount > 0 {
+      self.token_admin.mint(&user, &amount);
+    }
+    user
+  }
+
+  fn associations(&self, count: u32) -> Vec<Address> {
+    let mut associations: Vec<Address> = Vec::new(&self.env);
+    for _ in 0..count {
+      associations.push_back(Address::random(&self.env));
+    }
+    associations
+  }
+
+  fn create_round(&self, associations: &Vec<Address>, config: &MatchingConfig) -> u32 {
+    self.contract.create_round(&self.admin, &self.token.address, associations, &DEADLINE, config)
+  }
+
+  fn set_distribution_wasm(&self) {
+    let wasm_hash: BytesN<32> = self.env.deployer().upload_contract_wasm(distribution::WASM);
+    self.contract.set_distribution_wasm(&self.admin, &wasm_hash);
+  }
+
+  fn end_donations(&self) {
+    self.env.ledger().with_mut(|ledger| ledger.timestamp = DEADLINE + 1);
+  }
+
+  //Function to take a round from the end of the donations to the payout, once the challenge period is over.
+  fn approve(&self, round_id: u32) {
+    self.contract.calculate_funding(&self.admin, &round_id);
+    self.contract.end_funding(&self.admin, &round_id);
+    self.env.ledger().with_mut(|ledger| ledger.timestamp += DEFAULT_CHALLENGE_PERIOD);
+    self.contract.start_payout(&self.admin, &round_id);
+  }
+
+  //Function to tally every pair of donors of a round in the pairwise mode, by pages of the biggest size.
+  fn tally(&self, round_id: u32) {
+    while self.contract.tally_pairs(&round_id, &MAX_PAGE_SIZE) > 0 {}
+  }
+
+  fn matched(&self, round_id: u32) -> std::vec::Vec<i128> {
+    self.query
+      .total_final_associations(&round_id)
+      .iter()
+      .map(|association| association.matched)
+      .collect()
+  }
+}
+
+fn config() -> MatchingConfig {
+  MatchingConfig {
+    precision: 7,
+    mode: MatchingMode::Quadratic,
+    pairwise_threshold: 0,
+    cap: 0,
+    cap_bps: 0,
+  }
+}
+
+#[test]
+fn test_fixed_point_shares_add_up_to_the_matching_pool() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(3);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  for (association, amount) in associations.iter().zip([9_i64, 4, 1]) {
+    setup.contract.deposit(&setup.user(amount as i128), &round_id, &association, &amount, &None);
+  }
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+
+  // 100 * 9/14, 100 * 4/14 and 100 * 1/14 truncated once, the dust goes to the biggest weight.
+  assert_eq!(setup.matched(round_id), [65, 28, 7]);
+  let total: i128 = setup.query.total_final_associations(&round_id).iter().map(|association| association.contribution).sum();
+  assert_eq!(total, 114);
+}
+
+#[test]
+fn test_create_round_rejects_precision_out_of_range() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  for precision in [0, MAX_PRECISION + 1] {
+    let config = MatchingConfig { precision, ..config() };
+    let result = setup.contract.try_create_round(&setup.admin, &setup.token.address, &associations, &DEADLINE, &config);
+    assert_eq!(result, Err(Ok(Error::InvalidPrecision)));
+  }
+  assert_eq!(setup.create_round(&associations, &MatchingConfig { precision: MAX_PRECISION, ..config() }), 0);
+}
+
+#[test]
+fn test_unmatched_pool_is_returned_to_the_sponsors() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  // Nobody is attested by the verifier, so no donation counts for the matching.
+  setup.manager.set_verifier(&setup.admin, &round_id, &BytesN::from_array(&setup.env, &[1; 32]));
+  let sponsor: Address = setup.user(60);
+  let other_sponsor: Address = setup.user(40);
+  setup.contract.fund_matching_pool(&sponsor, &round_id, &60);
+  setup.contract.fund_matching_pool(&other_sponsor, &round_id, &40);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&setup.user(50), &round_id, &associations.get_unchecked(0), &50, &None);
+  setup.end_donations();
+  setup.approve(round_id);
+  assert_eq!(setup.matched(round_id), [0, 0]);
+
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  assert_eq!(setup.token.balance(&sponsor), 0);
+  assert_eq!(setup.contract.return_unmatched(&round_id, &1), 1);
+  assert_eq!(setup.contract.return_unmatched(&round_id, &1), 0);
+  let distribution_address: Address = setup.query.distribution_address(&round_id).unwrap();
+  assert_eq!(setup.token.balance(&distribution_address), 50);
+  assert_eq!(setup.token.balance(&sponsor), 60);
+  assert_eq!(setup.token.balance(&other_sponsor), 40);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_matching_pool_is_kept_apart_from_the_direct_donations() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let sponsor: Address = setup.user(30);
+  let other_sponsor: Address = setup.user(70);
+  setup.contract.fund_matching_pool(&sponsor, &round_id, &30);
+  setup.contract.fund_matching_pool(&other_sponsor, &round_id, &70);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&setup.user(4), &round_id, &associations.get_unchecked(0), &4, &None);
+  setup.contract.deposit(&setup.user(4), &round_id, &associations.get_unchecked(1), &4, &None);
+
+  assert_eq!(setup.query.matching_pool(&round_id), 100);
+  assert_eq!(setup.query.total_amount(&round_id), 8);
+  let sponsors: Map<Address, i128> = setup.query.sponsors(&round_id, &0, &10);
+  assert_eq!(sponsors.get(sponsor), Some(30));
+  assert_eq!(sponsors.get(other_sponsor), Some(70));
+
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  for association in setup.query.total_final_associations(&round_id).iter() {
+    assert_eq!(association.direct, 4);
+    assert_eq!(association.matched, 50);
+    assert_eq!(association.contribution, 54);
+  }
+}
+
+#[test]
+fn test_deposits_of_a_donor_are_aggregated() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  // 4 + 5 from one donor weighs sqrt(9)^2, the same as 9 from a single donor.
+  let donor: Address = setup.user(9);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &4, &None);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &5, &None);
+  setup.contract.deposit(&setup.user(9), &round_id, &associations.get_unchecked(1), &9, &None);
+
+  let donors: Map<Address, i128> = setup.query.association_donors(&round_id, &associations.get_unchecked(0));
+  assert_eq!(donors.len(), 1);
+  assert_eq!(donors.get(donor), Some(9));
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  assert_eq!(setup.matched(round_id), [50, 50]);
+}
+
+fn pairwise_config(threshold: i128) -> MatchingConfig {
+  MatchingConfig {
+    mode: MatchingMode::Pairwise,
+    pairwise_threshold: threshold,
+    ..config()
+  }
+}
+
+#[test]
+fn test_pairwise_mode_dampens_the_pairs_that_co_fund() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(3);
+  let round_id: u32 = setup.create_round(&associations, &pairwise_config(5));
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  // The first pair co-funds the first two associations, k = sqrt(4 * 4) + sqrt(1 * 1) = 5.
+  for _ in 0..2 {
+    let donor: Address = setup.user(5);
+    setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &4, &None);
+    setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(1), &1, &None);
+  }
+  // The second pair only funds the third association, k = 1.
+  for _ in 0..2 {
+    setup.contract.deposit(&setup.user(1), &round_id, &associations.get_unchecked(2), &1, &None);
+  }
+  setup.end_donations();
+  assert_eq!(setup.contract.try_calculate_funding(&setup.admin, &round_id), Err(Ok(Error::PairsNotTallied)));
+  // A page of 1 pair only tallies the first pair of the first association.
+  assert_eq!(setup.contract.tally_pairs(&round_id, &1), 3);
+  setup.tally(round_id);
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+
+  // The weights are 4 * 5/10 = 2, 1 * 5/10 = 0.5 and 1 * 5/6 = 0.8333333, the dust goes to the first one.
+  assert_eq!(setup.matched(round_id), [61, 15, 24]);
+}
+
+/*
+The budget of the pairwise mode with 10 associations and 20 donors funding every one of them,
+the worst case for the coordination since every pair of donors co-funds every association.
+A page of the tally and the calculation each have to fit in the default budget.
+*/
+#[test]
+fn test_pairwise_mode_fits_in_the_budget() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(10);
+  let round_id: u32 = setup.create_round(&associations, &pairwise_config(1000));
+  setup.contract.fund_matching_pool(&setup.user(1_000_000), &round_id, &1_000_000);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  for i in 0..20 {
+    let donor: Address = setup.user(10_000);
+    for association in associations.iter() {
+      setup.contract.deposit(&donor, &round_id, &association, &(100 + i), &None);
+    }
+  }
+  setup.end_donations();
+  setup.env.budget().reset_default();
+  setup.contract.tally_pairs(&round_id, &MAX_PAGE_SIZE);
+  assert!(setup.env.budget().cpu_instruction_cost() < 50_000_000);
+  setup.env.budget().reset_unlimited();
+  setup.tally(round_id);
+  setup.env.budget().reset_default();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  assert!(setup.env.budget().cpu_instruction_cost() < 50_000_000);
+}
+
+#[test]
+fn test_pairwise_mode_handles_big_donations_at_the_highest_precision() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let config = MatchingConfig { precision: MAX_PRECISION, ..pairwise_config(1) };
+  let round_id: u32 = setup.create_round(&associations, &config);
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  // 100000 tokens of 7 decimals each, c_i * c_j * scale^2 would be 10^42.
+  for _ in 0..2 {
+    setup.contract.deposit(&setup.user(1_000_000_000_000), &round_id, &associations.get_unchecked(0), &1_000_000_000_000, &None);
+  }
+  setup.end_donations();
+  setup.tally(round_id);
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  assert_eq!(setup.matched(round_id), [100]);
+}
+
+#[test]
+fn test_mul_div_keeps_the_product_in_256_bits() {
+  assert_eq!(mul_div(10_i128.pow(30), 10_i128.pow(30), 10_i128.pow(25)), Ok(10_i128.pow(35)));
+  assert_eq!(mul_div(i128::MAX, i128::MAX, i128::MAX), Ok(i128::MAX));
+  assert_eq!(mul_div(-7, 3, 2), Ok(-10));
+  assert_eq!(mul_div(i128::MAX, 2, 1), Err(Error::Overflow));
+}
+
+#[test]
+fn test_large_rounds_do_not_overflow_the_matching() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &MatchingConfig { precision: MAX_PRECISION, ..config() });
+  // A 10M tokens pool of 7 decimals.
+  setup.contract.fund_matching_pool(&setup.user(100_000_000_000_000), &round_id, &100_000_000_000_000);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  // The square of the sum of the square roots of the first association is 1.6 * 10^39 at the scale of 10^9.
+  let amount: i64 = 4_000_000_000_000_000_000;
+  for _ in 0..20 {
+    setup.contract.deposit(&setup.user(amount as i128), &round_id, &associations.get_unchecked(0), &amount, &None);
+  }
+  setup.contract.deposit(&setup.user(amount as i128), &round_id, &associations.get_unchecked(1), &amount, &None);
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+
+  // 10^14 * 400/401 and 10^14 * 1/401 truncated, the dust goes to the biggest weight.
+  assert_eq!(setup.matched(round_id), [99_750_623_441_397, 249_376_558_603]);
+}
+
+#[test]
+fn test_matching_cap_is_redistributed_to_the_uncapped_associations() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(3);
+  let round_id: u32 = setup.create_round(&associations, &MatchingConfig { cap: 40, ..config() });
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  for (association, amount) in associations.iter().zip([9_i64, 4, 1]) {
+    setup.contract.deposit(&setup.user(amount as i128), &round_id, &association, &amount, &None);
+  }
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+
+  // 64 goes over the cap, the 60 left are split 48 / 12, 48 goes over the cap and the last one gets the 20 left.
+  assert_eq!(setup.matched(round_id), [40, 40, 20]);
+  let capped: std::vec::Vec<bool> = setup.query.total_final_associations(&round_id).iter().map(|association| association.capped).collect();
+  assert_eq!(capped, [true, true, false]);
+}
+
+#[test]
+fn test_matching_left_when_every_association_is_capped_is_returned() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &MatchingConfig { cap_bps: 3000, ..config() });
+  let sponsor: Address = setup.user(100);
+  setup.contract.fund_matching_pool(&sponsor, &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  for association in associations.iter() {
+    setup.contract.deposit(&setup.user(10), &round_id, &association, &10, &None);
+  }
+  setup.end_donations();
+  setup.approve(round_id);
+  assert_eq!(setup.matched(round_id), [30, 30]);
+
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  let distribution_address: Address = setup.query.distribution_address(&round_id).unwrap();
+  let distribution = distribution::Client::new(&setup.env, &distribution_address);
+  assert_eq!(setup.token.balance(&distribution_address), 80);
+  assert_eq!(distribution.total(), 80);
+  assert_eq!(setup.contract.return_unmatched(&round_id, &10), 0);
+  assert_eq!(setup.token.balance(&sponsor), 40);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_queries_of_an_unknown_round_return_invalid_round() {
+  let setup = Setup::new();
+  let round_id: u32 = setup.create_round(&setup.associations(1), &config());
+  setup.contract.cancel_round(&setup.admin, &round_id);
+  assert_eq!(setup.query.state(&round_id), State::Cancelled as u32);
+  assert_eq!(setup.query.total_amount(&round_id), 0);
+
+  let unknown: u32 = round_id + 1;
+  assert_eq!(setup.query.try_deadline(&unknown), Err(Ok(Error::InvalidRound.into())));
+  assert_eq!(setup.query.try_total_amount(&unknown), Err(Ok(Error::InvalidRound.into())));
+  assert_eq!(setup.query.try_total_final_associations(&unknown), Err(Ok(Error::InvalidRound.into())));
+  assert_eq!(setup.query.try_matching_pool(&unknown), Err(Ok(Error::InvalidRound.into())));
+  assert_eq!(setup.manager.try_challenges(&unknown, &0, &10), Err(Ok(Error::InvalidRound.into())));
+}
+
+#[test]
+fn test_cancelled_round_refunds_donors_and_sponsors() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let sponsor: Address = setup.user(100);
+  let donor: Address = setup.user(30);
+  setup.contract.fund_matching_pool(&sponsor, &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &10, &None);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(1), &20, &None);
+  assert_eq!(setup.contract.try_refund(&donor, &round_id), Err(Ok(Error::InvalidRound)));
+
+  setup.contract.cancel_round(&setup.admin, &round_id);
+  assert_eq!(setup.contract.try_deposit(&donor, &round_id, &associations.get_unchecked(0), &1, &None), Err(Ok(Error::RoundCancelled)));
+  setup.contract.refund(&donor, &round_id);
+  setup.contract.refund(&sponsor, &round_id);
+  assert_eq!(setup.token.balance(&donor), 30);
+  assert_eq!(setup.token.balance(&sponsor), 100);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+  assert_eq!(setup.query.total_amount(&round_id), 0);
+  assert_eq!(setup.query.matching_pool(&round_id), 0);
+  assert_eq!(setup.contract.try_refund(&donor, &round_id), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_donations_of_a_suspended_association_are_refunded_before_the_withdraw() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let donor: Address = setup.user(30);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &10, &None);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(1), &20, &None);
+  setup.contract.suspend_association(&setup.admin, &round_id, &associations.get_unchecked(0), &true);
+  setup.end_donations();
+  assert_eq!(setup.contract.try_refund_association(&round_id, &associations.get_unchecked(0), &10), Err(Ok(Error::InvalidAssociation)));
+  setup.approve(round_id);
+
+  setup.set_distribution_wasm();
+  assert_eq!(setup.contract.try_withdraw(&setup.admin, &round_id), Err(Ok(Error::RefundsPending)));
+  assert_eq!(setup.contract.refund_association(&round_id, &associations.get_unchecked(0), &10), 0);
+  setup.contract.withdraw(&setup.admin, &round_id);
+  let distribution_address: Address = setup.query.distribution_address(&round_id).unwrap();
+  assert_eq!(setup.token.balance(&donor), 10);
+  assert_eq!(setup.token.balance(&distribution_address), 20);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_applications_are_closed_after_their_deadline() {
+  let setup = Setup::new();
+  let round_id: u32 = setup.create_round(&setup.associations(1), &config());
+  let metadata: String = String::from_slice(&setup.env, "ipfs://application");
+  setup.contract.open_applications(&setup.admin, &round_id, &500);
+  let association: Address = Address::random(&setup.env);
+  setup.manager.apply(&association, &round_id, &metadata);
+  assert_eq!(setup.manager.applications(&round_id, &manager::ApplicationStatus::Pending, &0, &10).len(), 1);
+
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp = 501);
+  let late: Address = Address::random(&setup.env);
+  assert_eq!(setup.manager.try_apply(&late, &round_id, &metadata), Err(Ok(Error::ApplicationsClosed.into())));
+}
+
+#[test]
+fn test_round_endpoints_follow_the_state_machine() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  assert_eq!(setup.query.state(&round_id), State::Setup as u32);
+  assert_eq!(setup.contract.try_deposit(&setup.user(10), &round_id, &associations.get_unchecked(0), &10, &None), Err(Ok(Error::InvalidState)));
+  assert_eq!(setup.contract.try_calculate_funding(&setup.admin, &round_id), Err(Ok(Error::InvalidState)));
+
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&setup.user(10), &round_id, &associations.get_unchecked(0), &10, &None);
+  assert_eq!(setup.contract.try_calculate_funding(&setup.admin, &round_id), Err(Ok(Error::InvalidTimestamp)));
+  setup.end_donations();
+  assert_eq!(setup.contract.try_start_payout(&setup.admin, &round_id), Err(Ok(Error::InvalidState)));
+
+  // A change of the associations after the calculation goes back to Ended, not to the donations.
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  let other: Address = setup.associations(1).get_unchecked(0);
+  setup.contract.suspend_association(&setup.admin, &round_id, &associations.get_unchecked(0), &false);
+  assert_eq!(setup.query.state(&round_id), State::Ended as u32);
+  assert_eq!(setup.contract.try_deposit(&setup.user(10), &round_id, &associations.get_unchecked(0), &10, &None), Err(Ok(Error::InvalidState)));
+  assert_eq!(setup.contract.try_add_association(&setup.admin, &round_id, &other), Err(Ok(Error::InvalidState)));
+  setup.approve(round_id);
+  assert_eq!(setup.query.state(&round_id), State::Payout as u32);
+
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  assert_eq!(setup.query.state(&round_id), State::Closed as u32);
+  assert_eq!(setup.contract.try_withdraw(&setup.admin, &round_id), Err(Ok(Error::AlreadyWithdrawn)));
+}
+
+#[test]
+fn test_unresolved_challenges_block_the_payout_after_a_recalculation() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  assert_eq!(setup.manager.try_set_challenge_period(&setup.admin, &round_id, &0), Err(Ok(Error::ChallengePeriod.into())));
+  let donor: Address = setup.user(10);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &10, &None);
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  setup.contract.end_funding(&setup.admin, &round_id);
+  let reason: String = String::from_slice(&setup.env, "The matching is wrong");
+  setup.manager.challenge(&donor, &round_id, &reason);
+
+  // Calculating again goes back to Calculated, the new review keeps the challenge open.
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  assert_eq!(setup.query.state(&round_id), State::Calculated as u32);
+  setup.contract.end_funding(&setup.admin, &round_id);
+  assert_eq!(setup.query.review_deadline(&round_id), DEADLINE + 1 + DEFAULT_CHALLENGE_PERIOD);
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp += DEFAULT_CHALLENGE_PERIOD);
+  assert_eq!(setup.contract.try_start_payout(&setup.admin, &round_id), Err(Ok(Error::UnresolvedChallenge)));
+
+  setup.manager.resolve_challenge(&setup.admin, &round_id, &donor, &false);
+  setup.contract.start_payout(&setup.admin, &round_id);
+  assert_eq!(setup.query.state(&round_id), State::Payout as u32);
+}
+
+#[test]
+fn test_upheld_challenge_sends_the_funding_back_to_be_calculated() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  let donor: Address = setup.user(20);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&donor, &round_id, &associations.get_unchecked(0), &10, &None);
+  setup.contract.deposit(&setup.user(10), &round_id, &associations.get_unchecked(1), &10, &None);
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  setup.contract.end_funding(&setup.admin, &round_id);
+  let reason: String = String::from_slice(&setup.env, "The second association is a fraud");
+  setup.manager.challenge(&donor, &round_id, &reason);
+  assert_eq!(setup.manager.try_challenge(&donor, &round_id, &reason), Err(Ok(Error::InvalidChallenge.into())));
+
+  // The challenge is upheld once the association is suspended, the funding is calculated again without it.
+  setup.contract.suspend_association(&setup.admin, &round_id, &associations.get_unchecked(1), &true);
+  setup.manager.resolve_challenge(&setup.admin, &round_id, &donor, &true);
+  assert_eq!(setup.query.state(&round_id), State::Ended as u32);
+  assert_eq!(setup.contract.try_start_payout(&setup.admin, &round_id), Err(Ok(Error::InvalidState)));
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  setup.contract.end_funding(&setup.admin, &round_id);
+  let challenge: manager::Challenge = setup.manager.challenges(&round_id, &0, &10).get_unchecked(0);
+  assert!(challenge.resolved && challenge.upheld);
+
+  // Every address can only challenge a round once, even in a new review.
+  assert_eq!(setup.manager.try_challenge(&donor, &round_id, &reason), Err(Ok(Error::InvalidChallenge.into())));
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp += DEFAULT_CHALLENGE_PERIOD);
+  setup.contract.start_payout(&setup.admin, &round_id);
+  assert_eq!(setup.matched(round_id), [100, 0]);
+}
+
+#[test]
+fn test_withdraw_deploys_a_funded_distribution_contract_per_round() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let other_round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&setup.user(10), &round_id, &associations.get_unchecked(0), &10, &None);
+  setup.contract.deposit(&setup.user(20), &round_id, &associations.get_unchecked(1), &20, &None);
+  setup.end_donations();
+  setup.approve(round_id);
+  assert_eq!(setup.contract.try_withdraw(&setup.admin, &round_id), Err(Ok(Error::DistributionNotSet)));
+  assert_eq!(setup.query.distribution_address(&round_id), None);
+
+  setup.set_distribution_wasm();
+  // The address of the distribution contract is known in advance, tokens sent there before the withdraw go back to the voting contract.
+  let salt: BytesN<32> = get_distribution_salt(&setup.env, round_id);
+  let distribution_address: Address = setup.env.deployer().with_address(setup.contract.address.clone(), salt).deployed_address();
+  setup.token_admin.mint(&distribution_address, &1);
+  let treasurer: Address = setup.user(0);
+  setup.manager.grant_role(&setup.admin, &manager::Role::Treasurer, &treasurer);
+  setup.contract.withdraw(&treasurer, &round_id);
+  assert_eq!(setup.query.distribution_address(&round_id), Some(distribution_address.clone()));
+  let distribution = distribution::Client::new(&setup.env, &distribution_address);
+  assert_eq!(setup.token.balance(&distribution_address), 30);
+  assert_eq!(setup.token.balance(&setup.contract.address), 1);
+  assert_eq!(distribution.total(), 30);
+  assert_eq!(distribution.associations().len(), 2);
+  assert_eq!(setup.query.distribution_address(&other_round_id), None);
+
+  // The treasurer only gets the treasurer role, the admin of the voting contract takes over once it accepts.
+  assert!(distribution.has_role(&distribution::Role::Treasurer, &treasurer));
+  assert!(!distribution.has_role(&distribution::Role::Operator, &treasurer));
+  assert_eq!(distribution.admin(), setup.contract.address);
+  assert_eq!(distribution.pending_admin(), Some(setup.admin.clone()));
+  distribution.accept_admin(&setup.admin);
+  assert_eq!(distribution.admin(), setup.admin);
+  assert!(!distribution.has_role(&distribution::Role::Owner, &setup.contract.address));
+}
+
+#[test]
+fn test_removed_association_publishes_a_refund_per_donor() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let removed: Address = associations.get_unchecked(0);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let first: Address = setup.user(10);
+  let second: Address = setup.user(20);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&first, &round_id, &removed, &10, &None);
+  setup.contract.deposit(&second, &round_id, &removed, &20, &None);
+
+  setup.contract.remove_association(&setup.admin, &round_id, &removed);
+  assert_eq!(setup.contract.try_add_association(&setup.admin, &round_id, &removed), Err(Ok(Error::RefundsPending)));
+  assert_eq!(setup.contract.refund_association(&round_id, &removed, &10), 0);
+  let events = setup.env.events().all();
+  for (donor, amount) in [(first, 10_i128), (second, 20)] {
+    assert_eq!(setup.token.balance(&donor), amount);
+    assert!(events.contains((
+      setup.contract.address.clone(),
+      (symbol_short!("refund"), EVENTS_VERSION, round_id).into_val(&setup.env),
+      (donor, amount).into_val(&setup.env),
+    )));
+  }
+  assert_eq!(setup.query.total_amount(&round_id), 0);
+  setup.contract.add_association(&setup.admin, &round_id, &removed);
+  assert_eq!(setup.query.association_donors(&round_id, &removed).len(), 0);
+}
+
+#[test]
+fn test_donor_index_is_split_in_chunks() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let association: Address = associations.get_unchecked(0);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let mut donors: std::vec::Vec<Address> = std::vec::Vec::new();
+  for _ in 0..DONOR_CHUNK_SIZE + 2 {
+    let donor: Address = setup.user(1);
+    setup.contract.deposit(&donor, &round_id, &association, &1, &None);
+    donors.push(donor);
+  }
+  let chunks = || setup.env.as_contract(&setup.contract.address, || get_donor_count(&setup.env, round_id, &association).div_ceil(DONOR_CHUNK_SIZE));
+  assert_eq!(chunks(), 2);
+
+  // The refunds of a removed association take the donors out of the end of the index, page by page.
+  setup.contract.remove_association(&setup.admin, &round_id, &association);
+  assert_eq!(setup.contract.refund_association(&round_id, &association, &1), DONOR_CHUNK_SIZE + 1);
+  assert_eq!(setup.token.balance(&donors[DONOR_CHUNK_SIZE as usize + 1]), 1);
+  assert_eq!(setup.token.balance(&donors[DONOR_CHUNK_SIZE as usize]), 0);
+  assert_eq!(chunks(), 2);
+  assert_eq!(setup.contract.refund_association(&round_id, &association, &1), DONOR_CHUNK_SIZE);
+  assert_eq!(chunks(), 1);
+  assert_eq!(setup.contract.refund_association(&round_id, &association, &DONOR_CHUNK_SIZE), 0);
+  assert_eq!(chunks(), 0);
+}
+
+#[test]
+fn test_ttl_config_is_capped_and_extend_ttl_is_paged() {
+  let setup = Setup::new();
+  let round_id: u32 = setup.create_round(&setup.associations(3), &config());
+  let too_long = TtlConfig { low: BUMP_LOW, high: MAX_TTL + 1 };
+  assert_eq!(setup.contract.try_set_ttl_config(&setup.admin, &too_long), Err(Ok(Error::InvalidTtl)));
+  let config = TtlConfig { low: BUMP_LOW, high: MAX_TTL };
+  setup.contract.set_ttl_config(&setup.admin, &config);
+  assert_eq!(setup.query.ttl_config(), query::TtlConfig { low: BUMP_LOW, high: MAX_TTL });
+
+  setup.contract.extend_ttl(&round_id, &0, &2);
+  setup.contract.extend_ttl(&round_id, &2, &2);
+  setup.contract.extend_ttl(&round_id, &10, &u32::MAX);
+  assert_eq!(setup.contract.try_extend_ttl(&(round_id + 1), &0, &2), Err(Ok(Error::InvalidRound)));
+}
+
+#[test]
+fn test_donors_of_an_association_are_bumped_by_pages() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let association: Address = associations.get_unchecked(0);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.open_donations(&setup.admin, &round_id);
+  for _ in 0..DONOR_CHUNK_SIZE + 2 {
+    setup.contract.deposit(&setup.user(1), &round_id, &association, &1, &None);
+  }
+  // Every page fits in the default budget, the second one spans both chunks of the DonorIndex.
+  setup.env.budget().reset_default();
+  setup.contract.extend_ttl(&round_id, &0, &MAX_PAGE_SIZE);
+  for offset in [0, DONOR_CHUNK_SIZE - 1] {
+    setup.env.budget().reset_default();
+    setup.contract.extend_association_ttl(&round_id, &association, &offset, &MAX_PAGE_SIZE);
+  }
+  // A removed association is still bumped until its donors are refunded.
+  setup.contract.remove_association(&setup.admin, &round_id, &association);
+  setup.env.budget().reset_default();
+  setup.contract.extend_association_ttl(&round_id, &association, &0, &MAX_PAGE_SIZE);
+  assert_eq!(setup.contract.try_extend_association_ttl(&(round_id + 1), &association, &0, &2), Err(Ok(Error::InvalidRound)));
+}
+
+#[test]
+fn test_donors_of_an_association_are_paged() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let association: Address = associations.get_unchecked(1);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let mut donors: std::vec::Vec<Address> = std::vec::Vec::new();
+  for amount in 1..=DONOR_CHUNK_SIZE as i64 + 5 {
+    let donor: Address = setup.user(amount as i128);
+    setup.contract.deposit(&donor, &round_id, &association, &amount, &None);
+    donors.push(donor);
+  }
+
+  let summaries: Vec<query::AssociationSummary> = setup.query.associations_amounts_page(&round_id, &1, &10);
+  assert_eq!(summaries.len(), 1);
+  assert_eq!(summaries.get_unchecked(0), query::AssociationSummary { name: association.clone(), donors: DONOR_CHUNK_SIZE + 5, suspended: false });
+
+  // The page crosses from the first entry of the DonorIndex to the second one.
+  let page: Map<Address, i128> = setup.query.association_donors_page(&round_id, &association, &(DONOR_CHUNK_SIZE - 2), &4);
+  assert_eq!(page.len(), 4);
+  for i in DONOR_CHUNK_SIZE - 2..DONOR_CHUNK_SIZE + 2 {
+    assert_eq!(page.get(donors[i as usize].clone()), Some(i as i128 + 1));
+  }
+  assert_eq!(setup.query.association_donors_page(&round_id, &association, &0, &u32::MAX).len(), MAX_PAGE_SIZE);
+  assert_eq!(setup.query.association_donors_page(&round_id, &association, &(DONOR_CHUNK_SIZE + 5), &10).len(), 0);
+}
+
+#[test]
+fn test_donations_in_another_token_are_valued_by_the_oracle() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let other_address: Address = setup.env.register_stellar_asset_contract(setup.admin.clone());
+  let other = token::Client::new(&setup.env, &other_address);
+  let oracle_address: Address = setup.env.register_contract_wasm(None, oracle::WASM);
+  let oracle = oracle::Client::new(&setup.env, &oracle_address);
+  oracle.init(&setup.admin, &7);
+  // One unit of the other token is worth two units of the base token.
+  oracle.set_price(&setup.admin, &setup.token.address, &other_address, &20_000_000);
+  let token_config = manager::TokenConfig { oracle: oracle_address, tokens: Vec::from_array(&setup.env, [other_address.clone()]), max_price_age: 100 };
+  setup.manager.set_token_config(&setup.admin, &round_id, &token_config);
+  setup.contract.open_donations(&setup.admin, &round_id);
+
+  let donor: Address = Address::random(&setup.env);
+  token::StellarAssetClient::new(&setup.env, &other_address).mint(&donor, &15);
+  setup.contract.deposit_token(&donor, &round_id, &associations.get_unchecked(0), &other_address, &10, &None);
+  setup.contract.deposit(&setup.user(20), &round_id, &associations.get_unchecked(1), &20, &None);
+  assert_eq!(setup.query.association_donors(&round_id, &associations.get_unchecked(0)).get(donor.clone()), Some(20));
+
+  // The price is too old once max_price_age is over.
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp = 101);
+  assert_eq!(setup.contract.try_deposit_token(&donor, &round_id, &associations.get_unchecked(0), &other_address, &5, &None), Err(Ok(Error::InvalidPrice)));
+
+  setup.end_donations();
+  setup.approve(round_id);
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  let distribution_address: Address = setup.query.distribution_address(&round_id).unwrap();
+  assert_eq!(other.balance(&associations.get_unchecked(0)), 10);
+  assert_eq!(setup.token.balance(&distribution_address), 20);
+  assert_eq!(other.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_every_memo_is_kept_and_only_the_public_ones_are_published() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let association: Address = associations.get_unchecked(0);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let donor: Address = setup.user(30);
+  let memo = |text: &str, public: bool| Memo { text: String::from_slice(&setup.env, text), public };
+  setup.contract.deposit(&donor, &round_id, &association, &10, &Some(memo("first", true)));
+  setup.contract.deposit(&donor, &round_id, &association, &10, &Some(memo("secret", false)));
+  let private_event = (
+    setup.contract.address.clone(),
+    (symbol_short!("deposit"), EVENTS_VERSION, round_id).into_val(&setup.env),
+    (donor.clone(), association.clone(), 10_i64, None::<Memo>).into_val(&setup.env),
+  );
+  assert!(setup.env.events().all().contains(private_event));
+  setup.contract.deposit(&donor, &round_id, &association, &10, &Some(memo("third", true)));
+
+  assert_eq!(setup.query.memo_count(&round_id, &association), 3);
+  let public_memo = |text: &str| query::DepositMemo { donor: donor.clone(), memo: query::Memo { text: String::from_slice(&setup.env, text), public: true } };
+  let memos: Vec<query::DepositMemo> = setup.query.memos(&round_id, &association, &0, &10);
+  assert_eq!(memos, Vec::from_array(&setup.env, [public_memo("first"), public_memo("third")]));
+  assert_eq!(setup.query.memos(&round_id, &association, &1, &1).len(), 0);
+}
+
+#[test]
+fn test_private_memos_are_left_out_of_the_token_deposit_events() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(1);
+  let association: Address = associations.get_unchecked(0);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let other_address: Address = setup.env.register_stellar_asset_contract(setup.admin.clone());
+  let oracle_address: Address = setup.env.register_contract_wasm(None, oracle::WASM);
+  let oracle = oracle::Client::new(&setup.env, &oracle_address);
+  oracle.init(&setup.admin, &7);
+  oracle.set_price(&setup.admin, &setup.token.address, &other_address, &10_000_000);
+  let token_config = manager::TokenConfig { oracle: oracle_address, tokens: Vec::from_array(&setup.env, [other_address.clone()]), max_price_age: 100 };
+  setup.manager.set_token_config(&setup.admin, &round_id, &token_config);
+  setup.contract.open_donations(&setup.admin, &round_id);
+
+  let donor: Address = Address::random(&setup.env);
+  token::StellarAssetClient::new(&setup.env, &other_address).mint(&donor, &10);
+  let memo = Memo { text: String::from_slice(&setup.env, "reference 42"), public: false };
+  setup.contract.deposit_token(&donor, &round_id, &association, &other_address, &10, &Some(memo));
+  assert!(setup.env.events().all().contains((
+    setup.contract.address.clone(),
+    (symbol_short!("dep_token"), EVENTS_VERSION, round_id).into_val(&setup.env),
+    (donor.clone(), association.clone(), other_address, 10_i64, 10_i128, None::<Memo>).into_val(&setup.env),
+  )));
+  assert_eq!(setup.query.memo_count(&round_id, &association), 1);
+  assert_eq!(setup.query.memos(&round_id, &association, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_commitments_add_up_bind_the_donor_and_are_settled_by_pages() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  setup.manager.set_commit_reveal(&setup.admin, &round_id, &manager::CommitRevealConfig { reveal_deadline: 2000, forfeit: false });
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let hash = |donor: &Address, allocations: &Map<Address, i128>, salt: &BytesN<32>| -> BytesN<32> {
+    let preimage = (setup.contract.address.clone(), round_id, donor.clone(), allocations.clone(), salt.clone());
+    setup.env.crypto().sha256(&preimage.to_xdr(&setup.env))
+  };
+  let salt: BytesN<32> = BytesN::from_array(&setup.env, &[7; 32]);
+
+  let donor: Address = setup.user(15);
+  let first = Map::from_array(&setup.env, [(associations.get_unchecked(0), 10_i128)]);
+  setup.contract.commit(&donor, &round_id, &hash(&donor, &first, &salt), &10);
+  // Committing again adds to the amount, the new hash covers the whole of it.
+  let allocations = Map::from_array(&setup.env, [(associations.get_unchecked(0), 10_i128), (associations.get_unchecked(1), 5)]);
+  setup.contract.commit(&donor, &round_id, &hash(&donor, &allocations, &salt), &5);
+  assert_eq!(setup.query.commitment(&round_id, &donor).unwrap().amount, 15);
+
+  // A copy of the hash of another donor can not be revealed, the hash is bound to the donor.
+  let copier: Address = setup.user(15);
+  setup.contract.commit(&copier, &round_id, &hash(&donor, &allocations, &salt), &15);
+  let idle: Address = setup.user(20);
+  setup.contract.commit(&idle, &round_id, &hash(&idle, &first, &salt), &20);
+
+  setup.end_donations();
+  assert_eq!(setup.contract.try_reveal(&copier, &round_id, &allocations, &salt), Err(Ok(Error::InvalidCommitment)));
+  setup.contract.reveal(&donor, &round_id, &allocations, &salt);
+  assert_eq!(setup.query.association_donors(&round_id, &associations.get_unchecked(1)).get(donor.clone()), Some(5));
+  // The revealed donor is taken out of the committers, only the two others are left to settle.
+  assert_eq!(setup.query.commitment(&round_id, &donor), None);
+  assert_eq!(setup.query.round_counts(&round_id).committers, 2);
+
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp = 2001);
+  assert_eq!(setup.contract.try_calculate_funding(&setup.admin, &round_id), Err(Ok(Error::InvalidCommitment)));
+  assert_eq!(setup.contract.settle_commitments(&round_id, &1), 1);
+  assert_eq!(setup.contract.settle_commitments(&round_id, &1), 0);
+  assert_eq!(setup.token.balance(&copier), 15);
+  assert_eq!(setup.token.balance(&idle), 20);
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+  assert_eq!(setup.query.total_amount(&round_id), 15);
+}
+
+#[test]
+fn test_forfeited_commitments_go_to_the_matching_pool_without_a_sponsor() {
+  let setup = Setup::new();
+  let round_id: u32 = setup.create_round(&setup.associations(2), &config());
+  setup.manager.set_commit_reveal(&setup.admin, &round_id, &manager::CommitRevealConfig { reveal_deadline: 2000, forfeit: true });
+  let sponsor: Address = setup.user(60);
+  setup.contract.fund_matching_pool(&sponsor, &round_id, &60);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let idle: Address = setup.user(40);
+  setup.contract.commit(&idle, &round_id, &BytesN::from_array(&setup.env, &[2; 32]), &40);
+  setup.end_donations();
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp = 2001);
+  assert_eq!(setup.contract.settle_commitments(&round_id, &10), 0);
+  assert_eq!(setup.query.matching_pool(&round_id), 100);
+  assert_eq!(setup.query.sponsors(&round_id, &0, &10).len(), 1);
+  setup.approve(round_id);
+
+  // Nothing is matched, the whole pool goes back to the only sponsor and nothing to the forfeiting donor.
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  assert_eq!(setup.contract.return_unmatched(&round_id, &10), 0);
+  assert_eq!(setup.token.balance(&sponsor), 100);
+  assert_eq!(setup.token.balance(&idle), 0);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_forfeited_commitments_are_refunded_when_the_round_is_cancelled() {
+  let setup = Setup::new();
+  let round_id: u32 = setup.create_round(&setup.associations(1), &config());
+  setup.manager.set_commit_reveal(&setup.admin, &round_id, &manager::CommitRevealConfig { reveal_deadline: 2000, forfeit: true });
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let idle: Address = setup.user(40);
+  setup.contract.commit(&idle, &round_id, &BytesN::from_array(&setup.env, &[2; 32]), &40);
+  setup.end_donations();
+  setup.env.ledger().with_mut(|ledger| ledger.timestamp = 2001);
+  setup.contract.settle_commitments(&round_id, &10);
+  assert_eq!(setup.query.matching_pool(&round_id), 40);
+
+  setup.contract.cancel_round(&setup.admin, &round_id);
+  setup.contract.refund(&idle, &round_id);
+  assert_eq!(setup.token.balance(&idle), 40);
+  assert_eq!(setup.query.matching_pool(&round_id), 0);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_pledges_match_within_their_budget_and_return_the_rest() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let sponsor: Address = setup.user(10);
+  setup.contract.pledge(&sponsor, &round_id, &5000, &10);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  setup.contract.deposit(&setup.user(30), &round_id, &associations.get_unchecked(0), &30, &None);
+  setup.contract.deposit(&setup.user(10), &round_id, &associations.get_unchecked(1), &10, &None);
+  setup.end_donations();
+  setup.approve(round_id);
+
+  // Half of the donations needs 20, the budget of 10 scales every match down by half.
+  assert_eq!(setup.matched(round_id), [7, 2]);
+  assert_eq!(setup.query.pledges(&round_id).get_unchecked(0).matched, 9);
+  setup.set_distribution_wasm();
+  setup.contract.withdraw(&setup.admin, &round_id);
+  let distribution_address: Address = setup.query.distribution_address(&round_id).unwrap();
+  assert_eq!(setup.token.balance(&distribution_address), 49);
+  assert_eq!(setup.token.balance(&sponsor), 1);
+  assert_eq!(setup.token.balance(&setup.contract.address), 0);
+}
+
+#[test]
+fn test_contribution_limits_are_enforced_per_deposit_association_and_donor() {
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let (first, second) = (associations.get_unchecked(0), associations.get_unchecked(1));
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let limits = manager::ContributionLimits { min_deposit: 5, max_per_donor: 25, max_per_association: 15 };
+  setup.manager.set_contribution_limits(&setup.admin, &round_id, &limits);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let donor: Address = setup.user(100);
+
+  assert_eq!(setup.contract.try_deposit(&donor, &round_id, &first, &4, &None), Err(Ok(Error::InvalidContribution)));
+  setup.contract.deposit(&donor, &round_id, &first, &10, &None);
+  assert_eq!(setup.contract.try_deposit(&donor, &round_id, &first, &6, &None), Err(Ok(Error::InvalidContribution)));
+  setup.contract.deposit(&donor, &round_id, &first, &5, &None);
+  setup.contract.deposit(&donor, &round_id, &second, &10, &None);
+  assert_eq!(setup.contract.try_deposit(&donor, &round_id, &second, &5, &None), Err(Ok(Error::InvalidContribution)));
+  assert_eq!(setup.token.balance(&donor), 75);
+  assert_eq!(setup.query.total_amount(&round_id), 25);
+}
+
+#[test]
+fn test_only_the_attestations_valid_at_the_deadline_are_matched() {
+  use ed25519_dalek::{Signer, SigningKey};
+  let setup = Setup::new();
+  let associations: Vec<Address> = setup.associations(2);
+  let round_id: u32 = setup.create_round(&associations, &config());
+  let verifier = SigningKey::from_bytes(&[7; 32]);
+  setup.manager.set_verifier(&setup.admin, &round_id, &BytesN::from_array(&setup.env, &verifier.verifying_key().to_bytes()));
+  setup.contract.fund_matching_pool(&setup.user(100), &round_id, &100);
+  setup.contract.open_donations(&setup.admin, &round_id);
+  let sign = |donor: &Address, expiry: u64| {
+    let message: Bytes = (setup.contract.address.clone(), donor.clone(), round_id, expiry).to_xdr(&setup.env);
+    let mut buffer: std::vec::Vec<u8> = std::vec![0; message.len() as usize];
+    message.copy_into_slice(&mut buffer);
+    BytesN::from_array(&setup.env, &verifier.sign(&buffer).to_bytes())
+  };
+  let (valid, expiring) = (setup.user(9), setup.user(9));
+  setup.contract.attest(&valid, &round_id, &DEADLINE, &sign(&valid, DEADLINE));
+  setup.contract.attest(&expiring, &round_id, &(DEADLINE - 1), &sign(&expiring, DEADLINE - 1));
+  assert!(setup.query.is_attested(&round_id, &valid));
+  assert!(!setup.query.is_attested(&round_id, &expiring));
+  setup.contract.deposit(&valid, &round_id, &associations.get_unchecked(0), &9, &None);
+  setup.contract.deposit(&expiring, &round_id, &associations.get_unchecked(1), &9, &None);
+  setup.end_donations();
+  setup.contract.calculate_funding(&setup.admin, &round_id);
+
+  // Only the donor attested until the deadline is matched, the other donation is still paid out directly.
+  assert_eq!(setup.matched(round_id), [100, 0]);
+  let total: i128 = setup.query.total_final_associations(&round_id).iter().map(|association| association.contribution).sum();
+  assert_eq!(total, 118);
+}
+
+#[test]
+fn test_admin_is_handed_over_in_two_steps_by_the_admin_only() {
+  let setup = Setup::new();
+  let (owner, new_admin) = (setup.user(0), setup.user(0));
+  setup.manager.grant_role(&setup.admin, &manager::Role::Owner, &owner);
+  assert_eq!(setup.manager.try_propose_admin(&owner, &new_admin), Err(Ok(Error::InvalidAuth.into())));
+  assert_eq!(setup.manager.try_cancel_admin(&setup.admin), Err(Ok(Error::InvalidAdmin.into())));
+  setup.manager.propose_admin(&setup.admin, &owner);
+  setup.manager.propose_admin(&setup.admin, &new_admin);
+  assert_eq!(setup.manager.pending_admin(), Some(new_admin.clone()));
+  assert_eq!(setup.manager.try_accept_admin(&owner), Err(Ok(Error::InvalidAuth.into())));
+
+  setup.manager.accept_admin(&new_admin);
+  assert_eq!(setup.manager.admin(), new_admin);
+  assert_eq!(setup.manager.pending_admin(), None);
+  assert!(setup.env.events().all().contains((
+    setup.manager.address.clone(),
+    (symbol_short!("accept"), EVENTS_VERSION).into_val(&setup.env),
+    (setup.admin.clone(), new_admin.clone()).into_val(&setup.env),
+  )));
+  // The old admin only keeps the roles granted to it, the owners keep theirs.
+  assert!(!setup.manager.has_role(&manager::Role::Operator, &setup.admin));
+  assert_eq!(setup.manager.try_grant_role(&setup.admin, &manager::Role::Operator, &setup.admin), Err(Ok(Error::InvalidAuth.into())));
+  assert!(setup.manager.has_role(&manager::Role::Operator, &owner));
+  assert_eq!(setup.manager.try_revoke_role(&owner, &manager::Role::Owner, &new_admin), Err(Ok(Error::InvalidRole.into())));
+  setup.manager.propose_admin(&new_admin, &owner);
+  setup.manager.cancel_admin(&new_admin);
+  assert_eq!(setup.manager.pending_admin(), None);
+}
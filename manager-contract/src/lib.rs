@@ -0,0 +1,1011 @@
+#![no_std]
+#[cfg(test)]
+extern crate std;
+
+use soroban_sdk::{
+  contract, contractimpl, contracttype, log, symbol_short, Address, BytesN, Env, IntoVal, String, Val, Vec,
+};
+
+use shared::{
+  check_round, check_round_exists, check_state, get_application_deadline, get_deadline, get_page_range, get_review_deadline,
+  get_round_config, get_token_address, get_ttl_config, is_association, is_donor, CommitRevealConfig, ContributionLimits,
+  Error, Role, RoundConfig, State, TokenConfig, TtlConfig, VotingClient, EVENTS_VERSION,
+};
+
+/*
+The manager contract of the voting contract (deployer-contract): it keeps the roles and the admin, checks the settings
+of the rounds before handing them to the voting contract, and runs the applications and the challenges,
+so the voting contract only keeps what moves the funds. The rounds themselves are read with the query contract.
+*/
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ApplicationStatus {
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Application{
+  pub name: Address,
+  pub metadata: String,
+  pub status: ApplicationStatus,
+}
+
+/*
+A challenge filed by an association or a donor against the calculated funding of a round.
+upheld: whether the reviewer who resolved it found it right, the funding was then calculated again.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge{
+  pub challenger: Address,
+  pub reason: String,
+  pub resolved: bool,
+  pub upheld: bool,
+}
+
+//The number of applications and challenges of a round, open_challenges being the challenges not resolved yet.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReviewCounts {
+  pub applications: u32,
+  pub challenges: u32,
+  pub open_challenges: u32,
+}
+
+/*
+The keys of this contract, the applications and the challenges of a round are kept in persistent storage, one entry per key:
+Application and Challenge: the entries of those lists by their position, their numbers are in Counts.
+Applicant and Challenger: the position of the entry of an address.
+The admin, the proposed one, the roles and the voting contract stay in instance storage.
+*/
+#[contracttype]
+pub enum ManagerConst {
+    AdminAddress,
+    PendingAdmin,
+    Voting,
+    Roles(Role),
+    Application(u32, u32),
+    Applicant(u32, Address),
+    Challenge(u32, u32),
+    Challenger(u32, Address),
+    Counts(u32),
+}
+
+//Highest length of the metadata an association can send with its application.
+const MAX_METADATA_LEN: u32 = 256;
+
+fn set_persistent<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(e: &Env, key: &K, value: &V) {
+  let config: TtlConfig = get_ttl_config(e, &get_voting(e));
+  e.storage().persistent().set(key, value);
+  e.storage().persistent().bump(key, config.low, config.high);
+}
+
+fn bump_persistent<K: IntoVal<Env, Val>>(e: &Env, key: &K, config: &TtlConfig) {
+  if e.storage().persistent().has(key) {
+    e.storage().persistent().bump(key, config.low, config.high);
+  }
+}
+
+fn bump_instance(e: &Env) {
+  let config: TtlConfig = get_ttl_config(e, &get_voting(e));
+  e.storage().instance().bump(config.low, config.high);
+}
+
+//Function to bump the counts of a round and a page of its applications and of its challenges.
+fn bump_review(e: &Env, round_id: u32, offset: u32, limit: u32) {
+  let config: TtlConfig = get_ttl_config(e, &get_voting(e));
+  bump_persistent(e, &ManagerConst::Counts(round_id), &config);
+  let counts: ReviewCounts = get_review_counts(e, round_id);
+  let (start, end) = get_page_range(counts.applications, offset, limit);
+  for i in start..end {
+      bump_persistent(e, &ManagerConst::Application(round_id, i), &config);
+      bump_persistent(e, &ManagerConst::Applicant(round_id, get_application(e, round_id, i).name), &config);
+  }
+  let (start, end) = get_page_range(counts.challenges, offset, limit);
+  for i in start..end {
+      bump_persistent(e, &ManagerConst::Challenge(round_id, i), &config);
+      bump_persistent(e, &ManagerConst::Challenger(round_id, get_challenge(e, round_id, i).challenger), &config);
+  }
+}
+
+/*
+Function to register the application of an association, a rejected association can apply again.
+*/
+fn add_application(e: &Env, round_id: u32, association: &Address, metadata: &String) -> Result<(), Error> {
+  let new_application: Application = Application {
+      name: association.clone(),
+      metadata: metadata.clone(),
+      status: ApplicationStatus::Pending,
+  };
+  let key: ManagerConst = ManagerConst::Applicant(round_id, association.clone());
+  if let Some(i) = e.storage().persistent().get::<_, u32>(&key) {
+      if get_application(e, round_id, i).status != ApplicationStatus::Rejected {
+          log!(
+            e,
+            "Something went wrong, the association has already applied."
+          );
+          return Err(Error::AlreadyApplied);
+      }
+      set_persistent(e, &ManagerConst::Application(round_id, i), &new_application);
+      return Ok(());
+  }
+  let mut counts: ReviewCounts = get_review_counts(e, round_id);
+  set_persistent(e, &ManagerConst::Application(round_id, counts.applications), &new_application);
+  set_persistent(e, &key, &counts.applications);
+  counts.applications += 1;
+  set_persistent(e, &ManagerConst::Counts(round_id), &counts);
+  Ok(())
+}
+
+//Function to approve or reject a pending application.
+fn review_application(e: &Env, round_id: u32, association: &Address, status: ApplicationStatus) -> Result<(), Error> {
+  if let Some(i) = e.storage().persistent().get::<_, u32>(&ManagerConst::Applicant(round_id, association.clone())) {
+      let mut application: Application = get_application(e, round_id, i);
+      if application.status == ApplicationStatus::Pending {
+          application.status = status;
+          set_persistent(e, &ManagerConst::Application(round_id, i), &application);
+          return Ok(());
+      }
+  }
+  log!(
+    e,
+    "Something went wrong, the association has no pending application."
+  );
+  Err(Error::InvalidApplication)
+}
+
+//Function to get the applications with a certain status among a page of the applications of a round.
+fn get_applications_page(e: &Env, round_id: u32, status: ApplicationStatus, offset: u32, limit: u32) -> Vec<Application> {
+  let (start, end) = get_page_range(get_review_counts(e, round_id).applications, offset, limit);
+  let mut filtered: Vec<Application> = Vec::new(e);
+  for i in start..end {
+      let application: Application = get_application(e, round_id, i);
+      if application.status == status {
+          filtered.push_back(application);
+      }
+  }
+  filtered
+}
+
+//Function to register a challenge, every challenger can only challenge a round once so the challenges stay bounded.
+fn add_challenge(e: &Env, round_id: u32, challenger: &Address, reason: &String) -> Result<(), Error> {
+  let key: ManagerConst = ManagerConst::Challenger(round_id, challenger.clone());
+  if e.storage().persistent().has(&key) {
+      log!(
+        e,
+        "Something went wrong, the address has already challenged the funding."
+      );
+      return Err(Error::InvalidChallenge);
+  }
+  let mut counts: ReviewCounts = get_review_counts(e, round_id);
+  set_persistent(e, &ManagerConst::Challenge(round_id, counts.challenges), &Challenge {
+      challenger: challenger.clone(),
+      reason: reason.clone(),
+      resolved: false,
+      upheld: false,
+  });
+  set_persistent(e, &key, &counts.challenges);
+  counts.challenges += 1;
+  counts.open_challenges += 1;
+  set_persistent(e, &ManagerConst::Counts(round_id), &counts);
+  Ok(())
+}
+
+fn resolve_challenge(e: &Env, round_id: u32, challenger: &Address, upheld: bool) -> Result<(), Error> {
+  if let Some(i) = e.storage().persistent().get::<_, u32>(&ManagerConst::Challenger(round_id, challenger.clone())) {
+      let mut challenge: Challenge = get_challenge(e, round_id, i);
+      if !challenge.resolved {
+          challenge.resolved = true;
+          challenge.upheld = upheld;
+          set_persistent(e, &ManagerConst::Challenge(round_id, i), &challenge);
+          let mut counts: ReviewCounts = get_review_counts(e, round_id);
+          counts.open_challenges -= 1;
+          set_persistent(e, &ManagerConst::Counts(round_id), &counts);
+          return Ok(());
+      }
+  }
+  log!(
+    e,
+    "Something went wrong, the address has no open challenge."
+  );
+  Err(Error::InvalidChallenge)
+}
+
+fn get_application(e: &Env, round_id: u32, i: u32) -> Application {
+  e.storage()
+    .persistent()
+    .get::<_, Application>(&ManagerConst::Application(round_id, i))
+    .expect("not initialized yet")
+}
+
+fn get_challenge(e: &Env, round_id: u32, i: u32) -> Challenge {
+  e.storage()
+    .persistent()
+    .get::<_, Challenge>(&ManagerConst::Challenge(round_id, i))
+    .expect("not initialized yet")
+}
+
+//Function to get a page of the challenges of a round.
+fn get_challenges_page(e: &Env, round_id: u32, offset: u32, limit: u32) -> Vec<Challenge> {
+  let (start, end) = get_page_range(get_review_counts(e, round_id).challenges, offset, limit);
+  let mut challenges: Vec<Challenge> = Vec::new(e);
+  for i in start..end {
+      challenges.push_back(get_challenge(e, round_id, i));
+  }
+  challenges
+}
+
+//The counts of the applications and the challenges of a round start at 0, see ReviewCounts.
+fn get_review_counts(e: &Env, round_id: u32) -> ReviewCounts {
+  e.storage()
+    .persistent()
+    .get::<_, ReviewCounts>(&ManagerConst::Counts(round_id))
+    .unwrap_or(ReviewCounts { applications: 0, challenges: 0, open_challenges: 0 })
+}
+
+fn get_admin_address(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&ManagerConst::AdminAddress)
+      .expect("not initialized yet")
+}
+
+//The admin proposed by propose_admin, until it accepts or the proposal is cancelled.
+fn get_pending_admin(e: &Env) -> Option<Address> {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&ManagerConst::PendingAdmin)
+}
+
+fn get_role_members(e: &Env, role: Role) -> Vec<Address> {
+  e.storage()
+      .instance()
+      .get::<_, Vec<Address>>(&ManagerConst::Roles(role))
+      .unwrap_or(Vec::new(e))
+}
+
+//The admin is always an owner, and the owners are allowed to do everything.
+fn has_role(e: &Env, address: &Address, role: Role) -> bool {
+  if get_admin_address(e) == *address || get_role_members(e, Role::Owner).contains(address) {
+    return true;
+  }
+  get_role_members(e, role).contains(address)
+}
+
+fn check_role(e: &Env, address: &Address, role: Role) -> Result<(), Error> {
+  if !has_role(e, address, role) {
+    log!(
+      e,
+      "Something went wrong, the address does not have the role needed to call the function."
+    );
+    return Err(Error::InvalidAuth);
+  }
+  Ok(())
+}
+
+fn get_voting(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&ManagerConst::Voting)
+      .expect("not initialized yet")
+}
+
+fn get_ledger_timestamp(e: &Env) -> u64 {
+  e.ledger().timestamp()
+}
+
+#[contract]
+pub struct ManagerContract;
+
+pub trait ManagerTrait{
+  /*
+  Initialize the contract, along with the voting contract it manages, it needs:
+  admin: admin address for both contracts, always an owner, who can grant the other roles (see Role).
+  voting: the address of the voting contract, not initialized yet.
+  */
+  fn init(
+    env: Env,
+    admin: Address,
+    voting: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to set the ed25519 public key of the verifier of a round, before the donations.
+  Only the donations of the donors attested by the verifier are then counted for the matching.
+  */
+  fn set_verifier(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    verifier: BytesN<32>,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an operator to set the contribution limits of a round, before the donations.
+  fn set_contribution_limits(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    limits: ContributionLimits,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an operator to turn on the commit-reveal mode of a round, before the donations.
+  fn set_commit_reveal(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    config: CommitRevealConfig,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an operator to set the tokens a round accepts besides its base token, before the donations.
+  fn set_token_config(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    config: TokenConfig,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an operator to set how many seconds the funding stays in review before the payout (3 days by default, not 0).
+  fn set_challenge_period(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    challenge_period: u64,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an association or a donor of a round to challenge its funding during the challenge period,
+  every address can only challenge a round once.
+  reason: why the funding is challenged (max 256 bytes).
+  */
+  fn challenge(
+    env: Env,
+    challenger: Address,
+    round_id: u32,
+    reason: String,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by a reviewer to resolve a challenge before the payout, it stays open until then
+  even if the funding is calculated again.
+  upheld: whether the challenge is right, the calculated funding is then discarded and the round goes back to Ended,
+  so it has to be calculated again (once the associations are fixed) and go through a new review.
+  */
+  fn resolve_challenge(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    challenger: Address,
+    upheld: bool,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an owner to grant a role to an address, see Role.
+  The owners are allowed to do everything, so granting them other roles changes nothing.
+  */
+  fn grant_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an owner to revoke a role granted to an address, the admin can not lose its ownership.
+  fn revoke_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an association to apply to a round while the applications are open.
+  metadata: a short description of the association (max 256 bytes).
+  */
+  fn apply(
+    env: Env,
+    association: Address,
+    round_id: u32,
+    metadata: String,
+  ) -> Result<(), Error>;
+
+  // This function will be called by a reviewer to approve an application, the association can then receive deposits.
+  fn approve_application(
+    env: Env,
+    reviewer: Address,
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by a reviewer to reject an application.
+  fn reject_application(
+    env: Env,
+    reviewer: Address,
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error>;
+
+  //This function will be called by anyone to get the applications with a certain status among a page of the applications of a round.
+  fn applications(
+    env: Env,
+    round_id: u32,
+    status: ApplicationStatus,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Application>, Error>;
+
+  /*
+  This function will be called by the admin to propose a new admin, which takes over once it calls accept_admin.
+  Only the admin itself can propose, not the other owners: the admin is the one owner that can not be revoked,
+  so an owner could otherwise take its place. A new proposal replaces the pending one.
+  The old admin only keeps the roles granted to it.
+  */
+  fn propose_admin(
+    env: Env,
+    admin: Address,
+    new_admin: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by the proposed admin to accept the proposal and become the admin.
+  fn accept_admin(
+    env: Env,
+    new_admin: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by the admin, and only by it like propose_admin, to cancel the pending proposal.
+  fn cancel_admin(
+    env: Env,
+    admin: Address,
+  ) -> Result<(), Error>;
+
+  //This function will be called by anyone to get the admin, the owner that can not be revoked and the one proposed to the distribution contracts.
+  fn admin(
+    env: Env,
+  ) -> Address;
+
+  //This function will be called by anyone to get the proposed admin, None when there is no pending proposal.
+  fn pending_admin(
+    env: Env,
+  ) -> Option<Address>;
+
+  //This function will be called by anyone to get the addresses granted a role, the admin is an owner besides them.
+  fn role_members(
+    env: Env,
+    role: Role,
+  ) -> Vec<Address>;
+
+  //This function will be called by anyone to know if an address has a role, which is always true for the owners.
+  fn has_role(
+    env: Env,
+    role: Role,
+    address: Address,
+  ) -> bool;
+
+  //This function will be called by anyone to get a page of the challenges of a round, with whether they were resolved and upheld.
+  fn challenges(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Challenge>, Error>;
+
+  //This function will be called by anyone to get the number of applications and challenges of a round, see ReviewCounts.
+  fn review_counts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<ReviewCounts, Error>;
+
+  //This function will be called by the voting contract before the payout, to know whether challenges are still open.
+  fn open_challenges(
+    env: Env,
+    round_id: u32,
+  ) -> u32;
+
+  /*
+  This function will be called by anyone to extend the time to live of the contract instance and of a page of the applications
+  and of the challenges of a round, for up to limit of each starting at offset (capped to 50).
+  */
+  fn extend_ttl(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error>;
+}
+
+#[contractimpl]
+impl ManagerTrait for ManagerContract {
+  fn init(
+    env: Env,
+    admin: Address,
+    voting: Address,
+  ) -> Result<(), Error> {
+    admin.require_auth();
+    if env.storage().instance().has(&ManagerConst::AdminAddress) {
+      log!(
+        &env,
+        "Something went wrong, the contract is already initialized."
+      );
+      return Err(Error::AlreadyInitialized);
+    }
+    env.storage().instance().set(&ManagerConst::AdminAddress, &admin);
+    env.storage().instance().set(&ManagerConst::Voting, &voting);
+    VotingClient::new(&env, &voting).init(&env.current_contract_address());
+    bump_instance(&env);
+    env.events().publish((symbol_short!("init"), EVENTS_VERSION), admin);
+    Ok(())
+  }
+
+  fn set_verifier(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    verifier: BytesN<32>,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, &voting, round_id, &[State::Setup, State::Applications])?;
+    let mut config: RoundConfig = get_round_config(&env, &voting, round_id);
+    config.verifier = Some(verifier.clone());
+    VotingClient::new(&env, &voting).configure(&round_id, &config.verifier, &config.limits, &config.commit_reveal, &config.tokens, &config.challenge_period);
+    env.events().publish((symbol_short!("verifier"), EVENTS_VERSION, round_id), verifier);
+    Ok(())
+  }
+
+  fn set_contribution_limits(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    limits: ContributionLimits,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, &voting, round_id, &[State::Setup, State::Applications])?;
+    if limits.min_deposit < 0 || limits.max_per_donor < 0 || limits.max_per_association < 0
+      || (limits.max_per_donor > 0 && limits.max_per_donor < limits.min_deposit)
+      || (limits.max_per_association > 0 && limits.max_per_association < limits.min_deposit) {
+      log!(
+        &env,
+        "Something went wrong, the limits are negative or a maximum is less than the minimum."
+      );
+      return Err(Error::InvalidContribution);
+    }
+    let mut config: RoundConfig = get_round_config(&env, &voting, round_id);
+    config.limits = Some(limits.clone());
+    VotingClient::new(&env, &voting).configure(&round_id, &config.verifier, &config.limits, &config.commit_reveal, &config.tokens, &config.challenge_period);
+    env.events().publish((symbol_short!("limits"), EVENTS_VERSION, round_id), limits);
+    Ok(())
+  }
+
+  fn set_commit_reveal(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    config: CommitRevealConfig,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, &voting, round_id, &[State::Setup, State::Applications])?;
+    if config.reveal_deadline <= get_deadline(&env, &voting, round_id) {
+      log!(
+        &env,
+        "Something went wrong, the reveal deadline is not after the deadline."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    let mut round_config: RoundConfig = get_round_config(&env, &voting, round_id);
+    round_config.commit_reveal = Some(config.clone());
+    VotingClient::new(&env, &voting).configure(&round_id, &round_config.verifier, &round_config.limits, &round_config.commit_reveal, &round_config.tokens, &round_config.challenge_period);
+    env.events().publish((symbol_short!("commit_rv"), EVENTS_VERSION, round_id), config);
+    Ok(())
+  }
+
+  fn set_token_config(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    config: TokenConfig,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, &voting, round_id, &[State::Setup, State::Applications])?;
+    if config.max_price_age == 0 {
+      log!(
+        &env,
+        "Something went wrong, the max price age is 0."
+      );
+      return Err(Error::InvalidPrice);
+    }
+    let base_token: Address = get_token_address(&env, &voting, round_id);
+    for (i, asset) in config.tokens.iter().enumerate() {
+      if asset == base_token || config.tokens.first_index_of(&asset) != Some(i as u32) {
+        log!(
+          &env,
+          "Something went wrong, the tokens contain the base token or a duplicate."
+        );
+        return Err(Error::InvalidToken);
+      }
+    }
+    let mut round_config: RoundConfig = get_round_config(&env, &voting, round_id);
+    round_config.tokens = Some(config.clone());
+    VotingClient::new(&env, &voting).configure(&round_id, &round_config.verifier, &round_config.limits, &round_config.commit_reveal, &round_config.tokens, &round_config.challenge_period);
+    env.events().publish((symbol_short!("tokens"), EVENTS_VERSION, round_id), config);
+    Ok(())
+  }
+
+  fn set_challenge_period(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    challenge_period: u64,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    check_state(&env, &voting, round_id, &[State::Setup, State::Applications, State::Donations, State::Ended, State::Calculated])?;
+    if challenge_period == 0 {
+      log!(
+        &env,
+        "Something went wrong, the challenge period is 0."
+      );
+      return Err(Error::ChallengePeriod);
+    }
+    let mut config: RoundConfig = get_round_config(&env, &voting, round_id);
+    config.challenge_period = challenge_period;
+    VotingClient::new(&env, &voting).configure(&round_id, &config.verifier, &config.limits, &config.commit_reveal, &config.tokens, &config.challenge_period);
+    env.events().publish((symbol_short!("chal_time"), EVENTS_VERSION, round_id), challenge_period);
+    Ok(())
+  }
+
+  fn challenge(
+    env: Env,
+    challenger: Address,
+    round_id: u32,
+    reason: String,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    challenger.require_auth();
+    check_state(&env, &voting, round_id, &[State::Review])?;
+    if get_review_deadline(&env, &voting, round_id) <= get_ledger_timestamp(&env) {
+      log!(
+        &env,
+        "Something went wrong, the challenge period is over."
+      );
+      return Err(Error::InvalidTimestamp);
+    }
+    if reason.len() > MAX_METADATA_LEN {
+      log!(
+        &env,
+        "Something went wrong, the reason is longer than the maximum allowed."
+      );
+      return Err(Error::InvalidMetadata);
+    }
+    if !is_association(&env, &voting, round_id, &challenger) && !is_donor(&env, &voting, round_id, &challenger) {
+      log!(
+        &env,
+        "Something went wrong, the address is neither an association nor a donor of the round."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    add_challenge(&env, round_id, &challenger, &reason)?;
+    env.events().publish((symbol_short!("challenge"), EVENTS_VERSION, round_id), (challenger, reason));
+    Ok(())
+  }
+
+  fn resolve_challenge(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    challenger: Address,
+    upheld: bool,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    admin.require_auth();
+    check_role(&env, &admin, Role::Reviewer)?;
+    check_state(&env, &voting, round_id, &[State::Ended, State::Calculated, State::Review])?;
+    resolve_challenge(&env, round_id, &challenger, upheld)?;
+    if upheld {
+      VotingClient::new(&env, &voting).reset_funding(&round_id);
+    }
+    env.events().publish((symbol_short!("resolved"), EVENTS_VERSION, round_id), (challenger, upheld));
+    Ok(())
+  }
+
+  fn grant_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    owner.require_auth();
+    check_role(&env, &owner, Role::Owner)?;
+    let mut members: Vec<Address> = get_role_members(&env, role);
+    if members.contains(&address) {
+      log!(
+        &env,
+        "Something went wrong, the address already has the role."
+      );
+      return Err(Error::InvalidRole);
+    }
+    members.push_back(address.clone());
+    env.storage().instance().set(&ManagerConst::Roles(role), &members);
+    env.events().publish((symbol_short!("grant"), EVENTS_VERSION), (role, address));
+    Ok(())
+  }
+
+  fn revoke_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    owner.require_auth();
+    check_role(&env, &owner, Role::Owner)?;
+    let mut members: Vec<Address> = get_role_members(&env, role);
+    match members.first_index_of(&address) {
+      Some(index) => members.remove(index),
+      None => {
+        log!(
+          &env,
+          "Something went wrong, the address does not have the role."
+        );
+        return Err(Error::InvalidRole);
+      }
+    };
+    env.storage().instance().set(&ManagerConst::Roles(role), &members);
+    env.events().publish((symbol_short!("revoke"), EVENTS_VERSION), (role, address));
+    Ok(())
+  }
+
+  fn apply(
+    env: Env,
+    association: Address,
+    round_id: u32,
+    metadata: String,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    association.require_auth();
+    check_state(&env, &voting, round_id, &[State::Applications])?;
+    let application_deadline: Option<u64> = get_application_deadline(&env, &voting, round_id);
+    if application_deadline.is_none_or(|deadline| deadline < get_ledger_timestamp(&env)) {
+      log!(
+        &env,
+        "Something went wrong, the applications of the round are closed."
+      );
+      return Err(Error::ApplicationsClosed);
+    }
+    if metadata.len() > MAX_METADATA_LEN {
+      log!(
+        &env,
+        "Something went wrong, the metadata is longer than the maximum allowed."
+      );
+      return Err(Error::InvalidMetadata);
+    }
+    if is_association(&env, &voting, round_id, &association) {
+      log!(
+        &env,
+        "Something went wrong, the association is already part of the round."
+      );
+      return Err(Error::AlreadyApplied);
+    }
+    add_application(&env, round_id, &association, &metadata)?;
+    env.events().publish((symbol_short!("apply"), EVENTS_VERSION, round_id), (association, metadata));
+    Ok(())
+  }
+
+  fn approve_application(
+    env: Env,
+    reviewer: Address,
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    reviewer.require_auth();
+    check_role(&env, &reviewer, Role::Reviewer)?;
+    check_state(&env, &voting, round_id, &[State::Applications])?;
+    review_application(&env, round_id, &association, ApplicationStatus::Approved)?;
+    VotingClient::new(&env, &voting).add_association(&env.current_contract_address(), &round_id, &association);
+    env.events().publish((symbol_short!("reviewed"), EVENTS_VERSION, round_id), (association, ApplicationStatus::Approved));
+    Ok(())
+  }
+
+  fn reject_application(
+    env: Env,
+    reviewer: Address,
+    round_id: u32,
+    association: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round(&env, &voting, round_id)?;
+    reviewer.require_auth();
+    check_role(&env, &reviewer, Role::Reviewer)?;
+    check_state(&env, &voting, round_id, &[State::Applications])?;
+    review_application(&env, round_id, &association, ApplicationStatus::Rejected)?;
+    env.events().publish((symbol_short!("reviewed"), EVENTS_VERSION, round_id), (association, ApplicationStatus::Rejected));
+    Ok(())
+  }
+
+  fn applications(
+    env: Env,
+    round_id: u32,
+    status: ApplicationStatus,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Application>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_applications_page(&env, round_id, status, offset, limit))
+  }
+
+  fn propose_admin(
+    env: Env,
+    admin: Address,
+    new_admin: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    admin.require_auth();
+    let admin_address: Address = get_admin_address(&env);
+    if admin_address != admin {
+      log!(
+        &env,
+        "Something went wrong, the admin address is not the same as the one who called the function."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    if new_admin == admin {
+      log!(
+        &env,
+        "Something went wrong, the new admin is already the admin."
+      );
+      return Err(Error::InvalidAdmin);
+    }
+    env.storage().instance().set(&ManagerConst::PendingAdmin, &new_admin);
+    env.events().publish((symbol_short!("propose"), EVENTS_VERSION), (admin, new_admin));
+    Ok(())
+  }
+
+  fn accept_admin(
+    env: Env,
+    new_admin: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    new_admin.require_auth();
+    if get_pending_admin(&env) != Some(new_admin.clone()) {
+      log!(
+        &env,
+        "Something went wrong, the address is not the proposed admin."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    let old_admin: Address = get_admin_address(&env);
+    env.storage().instance().set(&ManagerConst::AdminAddress, &new_admin);
+    env.storage().instance().remove(&ManagerConst::PendingAdmin);
+    env.events().publish((symbol_short!("accept"), EVENTS_VERSION), (old_admin, new_admin));
+    Ok(())
+  }
+
+  fn cancel_admin(
+    env: Env,
+    admin: Address,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    admin.require_auth();
+    let admin_address: Address = get_admin_address(&env);
+    if admin_address != admin {
+      log!(
+        &env,
+        "Something went wrong, the admin address is not the same as the one who called the function."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    let new_admin: Address = match get_pending_admin(&env) {
+      Some(new_admin) => new_admin,
+      None => {
+        log!(
+          &env,
+          "Something went wrong, there is no admin proposed."
+        );
+        return Err(Error::InvalidAdmin);
+      }
+    };
+    env.storage().instance().remove(&ManagerConst::PendingAdmin);
+    env.events().publish((symbol_short!("unpropose"), EVENTS_VERSION), new_admin);
+    Ok(())
+  }
+
+  fn admin(
+    env: Env,
+  ) -> Address {
+    get_admin_address(&env)
+  }
+
+  fn pending_admin(
+    env: Env,
+  ) -> Option<Address> {
+    get_pending_admin(&env)
+  }
+
+  fn role_members(
+    env: Env,
+    role: Role,
+  ) -> Vec<Address> {
+    get_role_members(&env, role)
+  }
+
+  fn has_role(
+    env: Env,
+    role: Role,
+    address: Address,
+  ) -> bool {
+    has_role(&env, &address, role)
+  }
+
+  fn challenges(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Challenge>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_challenges_page(&env, round_id, offset, limit))
+  }
+
+  fn review_counts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<ReviewCounts, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_review_counts(&env, round_id))
+  }
+
+  fn open_challenges(
+    env: Env,
+    round_id: u32,
+  ) -> u32 {
+    get_review_counts(&env, round_id).open_challenges
+  }
+
+  fn extend_ttl(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<(), Error> {
+    bump_instance(&env);
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    bump_review(&env, round_id, offset, limit);
+    Ok(())
+  }
+}
@@ -0,0 +1,16 @@
+## A price oracle with prices set by hand, to try out the donations in other tokens of the voting contract.
+
+## build the contract.
+soroban contract build
+
+## Deploy the contract to the testnet.
+soroban contract deploy --wasm target/wasm32-unknown-unknown/release/mock_oracle_contract.wasm --source cashabroad --network testnet
+
+## Invoke the init function, the prices have 7 decimal digits.
+soroban contract invoke --id ___ --source cashabroad --network testnet -- init --admin cashabroad --decimals 7
+
+## Set the price of one unit of token in units of base (here 1.08 base per token), it is timestamped now.
+soroban contract invoke --id ___ --source cashabroad --network testnet -- set_price --admin cashabroad --base CB64D3G7SM2RTH6JSGG34DDTFTQ5CFDKVDZJZSODMCX4NJ2HV2KN7OHT --token ___ --price 10800000
+
+## Get the last price of a token.
+soroban contract invoke --id ___ --source cashabroad --network testnet -- lastprice --base CB64D3G7SM2RTH6JSGG34DDTFTQ5CFDKVDZJZSODMCX4NJ2HV2KN7OHT --token ___
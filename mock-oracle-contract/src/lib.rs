@@ -0,0 +1,157 @@
+#![no_std]
+
+#[cfg(test)]
+extern crate std;
+
+use soroban_sdk::{
+  contract, contracterror, contractimpl, contracttype, log, symbol_short,
+  Address, Env,
+};
+
+/*
+A price oracle with prices set by hand, implementing the PriceOracleInterface of the voting contract
+so the donations in other tokens can be tried out locally or on the testnet.
+*/
+
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error{
+  InvalidAuth = 1,
+  AlreadyInitialized = 2,
+  InvalidPrice = 3,
+}
+
+//The same fields as the PriceData of the voting contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+  pub price: i128,
+  pub timestamp: u64,
+}
+
+#[contracttype]
+pub enum StorageConst {
+    AdminAddress,
+    Decimals,
+    Price(Address, Address),
+}
+
+//Version of the events, init: (admin, decimals). price: (base, token, price).
+const EVENTS_VERSION: u32 = 1;
+
+fn get_admin_address(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&StorageConst::AdminAddress)
+      .expect("not initialized yet")
+}
+
+fn get_decimals(e: &Env) -> u32 {
+  e.storage()
+      .instance()
+      .get::<_, u32>(&StorageConst::Decimals)
+      .expect("not initialized yet")
+}
+
+fn get_price(e: &Env, base: &Address, token: &Address) -> Option<PriceData> {
+  e.storage()
+      .instance()
+      .get::<_, PriceData>(&StorageConst::Price(base.clone(), token.clone()))
+}
+
+#[contract]
+pub struct MockOracleContract;
+
+pub trait MockOracleTrait{
+  // This function will be called once by the admin to set itself as admin and the number of decimal digits of the prices.
+  fn init(
+    env: Env,
+    admin: Address,
+    decimals: u32,
+  ) -> Result<(), Error>;
+
+  // This function will be called by the admin to set the price of one unit of token in units of base, it is timestamped now.
+  fn set_price(
+    env: Env,
+    admin: Address,
+    base: Address,
+    token: Address,
+    price: i128,
+  ) -> Result<(), Error>;
+
+  fn lastprice(
+    env: Env,
+    base: Address,
+    token: Address,
+  ) -> Option<PriceData>;
+
+  fn decimals(
+    env: Env,
+  ) -> u32;
+}
+
+#[contractimpl]
+impl MockOracleTrait for MockOracleContract{
+  fn init(
+    env: Env,
+    admin: Address,
+    decimals: u32,
+  ) -> Result<(), Error>{
+    admin.require_auth();
+    if env.storage().instance().has(&StorageConst::AdminAddress) {
+      log!(
+        &env,
+        "Something went wrong, the contract has already been initialized."
+      );
+      return Err(Error::AlreadyInitialized);
+    }
+    env.storage().instance().set(&StorageConst::AdminAddress, &admin);
+    env.storage().instance().set(&StorageConst::Decimals, &decimals);
+    env.events().publish((symbol_short!("init"), EVENTS_VERSION), (admin, decimals));
+    Ok(())
+  }
+
+  fn set_price(
+    env: Env,
+    admin: Address,
+    base: Address,
+    token: Address,
+    price: i128,
+  ) -> Result<(), Error>{
+    admin.require_auth();
+    let admin_address: Address = get_admin_address(&env);
+    if admin_address != admin {
+      log!(
+        &env,
+        "Something went wrong, the admin address is not the same as the one who called the function."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    if price <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the price is 0 or less than 0."
+      );
+      return Err(Error::InvalidPrice);
+    }
+    let price_data: PriceData = PriceData{price, timestamp: env.ledger().timestamp()};
+    env.storage().instance().set(&StorageConst::Price(base.clone(), token.clone()), &price_data);
+    env.events().publish((symbol_short!("price"), EVENTS_VERSION), (base, token, price));
+    Ok(())
+  }
+
+  fn lastprice(
+    env: Env,
+    base: Address,
+    token: Address,
+  ) -> Option<PriceData>{
+    get_price(&env, &base, &token)
+  }
+
+  fn decimals(
+    env: Env,
+  ) -> u32{
+    get_decimals(&env)
+  }
+}
@@ -0,0 +1,799 @@
+#![no_std]
+#[cfg(test)]
+extern crate std;
+
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, BytesN, Env, Map, Vec};
+
+use shared::{
+  check_round_exists, get_deadline, get_entry, get_page_range, get_review_deadline, get_round_count,
+  get_state, get_ttl_config, is_association, CommitRevealConfig, Commitment, ContributionLimits, DepositMemo, Error,
+  FinalAssociation, MatchingConfig, Pledge, RoundCounts, StorageConst, TokenConfig, TokenDonation, TtlConfig,
+  DONOR_CHUNK_SIZE,
+};
+
+/*
+The query contract of the voting contract (deployer-contract): it answers the queries about the rounds
+by reading the entries of the voting contract, it keeps nothing but the address of the voting contract.
+The roles, the applications and the challenges are queried from the manager contract.
+*/
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Association{
+  pub name: Address,
+  pub contribution: Map<Address, i128>,
+  pub suspended: bool,
+}
+
+//An association of a round returned by associations_amounts_page, its donations are paged by association_donors_page.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssociationSummary{
+  pub name: Address,
+  pub donors: u32,
+  pub suspended: bool,
+}
+
+#[contracttype]
+pub enum QueryConst {
+    Voting,
+}
+
+fn get_voting(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&QueryConst::Voting)
+      .expect("not initialized yet")
+}
+
+//The distribution contract of a round is only deployed by withdraw.
+fn get_contract_call_address(e: &Env, voting: &Address, round_id: u32) -> Option<Address> {
+  get_entry(e, voting, StorageConst::ContractCallAddress(round_id))
+}
+
+//The counts of the lists of a round in the voting contract start at 0, see RoundCounts.
+fn get_round_counts(e: &Env, voting: &Address, round_id: u32) -> RoundCounts {
+  get_entry(e, voting, StorageConst::Counts(round_id)).unwrap_or(RoundCounts { sponsors: 0, pledges: 0, committers: 0, settled: 0 })
+}
+
+fn get_association_index(e: &Env, voting: &Address, round_id: u32) -> Vec<Address> {
+  get_entry(e, voting, StorageConst::AssociationIndex(round_id)).expect("not initialized yet")
+}
+
+fn is_suspended(e: &Env, voting: &Address, round_id: u32, association: &Address) -> bool {
+  get_entry(e, voting, StorageConst::AssociationSuspended(round_id, association.clone())).unwrap_or(false)
+}
+
+fn get_donations(e: &Env, voting: &Address, round_id: u32, donor: &Address) -> Map<Address, i128> {
+  get_entry(e, voting, StorageConst::Donations(round_id, donor.clone())).unwrap_or(Map::new(e))
+}
+
+fn get_contribution(e: &Env, voting: &Address, round_id: u32, association: &Address, donor: &Address) -> i128 {
+  get_donations(e, voting, round_id, donor).get(association.clone()).unwrap_or(0)
+}
+
+fn get_donor_count(e: &Env, voting: &Address, round_id: u32, association: &Address) -> u32 {
+  get_entry(e, voting, StorageConst::DonorCount(round_id, association.clone())).unwrap_or(0)
+}
+
+fn get_donor_chunk_count(e: &Env, voting: &Address, round_id: u32, association: &Address) -> u32 {
+  get_donor_count(e, voting, round_id, association).div_ceil(DONOR_CHUNK_SIZE)
+}
+
+fn get_donor_chunk(e: &Env, voting: &Address, round_id: u32, association: &Address, chunk: u32) -> Vec<Address> {
+  get_entry(e, voting, StorageConst::DonorIndex(round_id, association.clone(), chunk)).unwrap_or(Vec::new(e))
+}
+
+//Function to get every donor of an association, reading each entry of the DonorIndex.
+fn get_donors(e: &Env, voting: &Address, round_id: u32, association: &Address) -> Vec<Address> {
+  let mut donors: Vec<Address> = Vec::new(e);
+  for chunk in 0..get_donor_chunk_count(e, voting, round_id, association) {
+      donors.append(&get_donor_chunk(e, voting, round_id, association, chunk));
+  }
+  donors
+}
+
+fn get_contributions_per_association(e: &Env, voting: &Address, round_id: u32, name: &Address) -> Map<Address, i128>{
+  let mut contributions: Map<Address, i128> = Map::new(e);
+  if !is_association(e, voting, round_id, name) {
+      return contributions;
+  }
+  for donor in get_donors(e, voting, round_id, name).iter() {
+      let amount: i128 = get_contribution(e, voting, round_id, name, &donor);
+      if amount > 0 {
+          contributions.set(donor, amount);
+      }
+  }
+  contributions
+}
+
+//Function to put together an association of a round with every donation it received.
+fn get_association(e: &Env, voting: &Address, round_id: u32, name: &Address) -> Association {
+  Association {
+      name: name.clone(),
+      contribution: get_contributions_per_association(e, voting, round_id, name),
+      suspended: is_suspended(e, voting, round_id, name),
+  }
+}
+
+//Function to put together every association of a round.
+fn get_associations(e: &Env, voting: &Address, round_id: u32) -> Vec<Association> {
+  let mut associations: Vec<Association> = Vec::new(e);
+  for name in get_association_index(e, voting, round_id).iter() {
+      associations.push_back(get_association(e, voting, round_id, &name));
+  }
+  associations
+}
+
+//Function to put together a page of the associations of a round, so the query only reads the entries it returns.
+fn get_associations_page(e: &Env, voting: &Address, round_id: u32, offset: u32, limit: u32) -> Vec<AssociationSummary> {
+  let index: Vec<Address> = get_association_index(e, voting, round_id);
+  let (start, end) = get_page_range(index.len(), offset, limit);
+  let mut associations: Vec<AssociationSummary> = Vec::new(e);
+  for name in index.slice(start..end).iter() {
+      associations.push_back(AssociationSummary {
+          donors: get_donor_count(e, voting, round_id, &name),
+          suspended: is_suspended(e, voting, round_id, &name),
+          name,
+      });
+  }
+  associations
+}
+
+//Function to get the contribution of a page of the donors of an association, reading only the entries of the DonorIndex it needs.
+fn get_donors_page(e: &Env, voting: &Address, round_id: u32, association: &Address, offset: u32, limit: u32) -> Map<Address, i128> {
+  let (start, end) = get_page_range(get_donor_count(e, voting, round_id, association), offset, limit);
+  let mut contributions: Map<Address, i128> = Map::new(e);
+  let mut chunk: u32 = start / DONOR_CHUNK_SIZE;
+  let mut donors: Vec<Address> = get_donor_chunk(e, voting, round_id, association, chunk);
+  for i in start..end {
+      if i / DONOR_CHUNK_SIZE != chunk {
+          chunk = i / DONOR_CHUNK_SIZE;
+          donors = get_donor_chunk(e, voting, round_id, association, chunk);
+      }
+      let donor: Address = donors.get_unchecked(i % DONOR_CHUNK_SIZE);
+      contributions.set(donor.clone(), get_contribution(e, voting, round_id, association, &donor));
+  }
+  contributions
+}
+
+fn get_final_association_count(e: &Env, voting: &Address, round_id: u32) -> u32 {
+  get_entry(e, voting, StorageConst::FinalAssociationCount(round_id)).unwrap_or(0)
+}
+
+fn get_final_association(e: &Env, voting: &Address, round_id: u32, i: u32) -> FinalAssociation {
+  get_entry(e, voting, StorageConst::FinalAssociation(round_id, i)).expect("not initialized yet")
+}
+
+fn get_final_associations(e: &Env, voting: &Address, round_id: u32) -> Vec<FinalAssociation> {
+  let mut associations: Vec<FinalAssociation> = Vec::new(e);
+  for i in 0..get_final_association_count(e, voting, round_id) {
+      associations.push_back(get_final_association(e, voting, round_id, i));
+  }
+  associations
+}
+
+//Function to get the sponsors of a page of the SponsorIndex with the amount each one put in the matching pool.
+fn get_sponsors_page(e: &Env, voting: &Address, round_id: u32, offset: u32, limit: u32) -> Map<Address, i128> {
+  let (start, end) = get_page_range(get_round_counts(e, voting, round_id).sponsors, offset, limit);
+  let mut sponsors: Map<Address, i128> = Map::new(e);
+  for i in start..end {
+      let sponsor: Address = get_sponsor(e, voting, round_id, i);
+      sponsors.set(sponsor.clone(), get_sponsor_amount(e, voting, round_id, &sponsor));
+  }
+  sponsors
+}
+
+fn get_sponsor(e: &Env, voting: &Address, round_id: u32, i: u32) -> Address {
+  get_entry(e, voting, StorageConst::SponsorIndex(round_id, i)).expect("not initialized yet")
+}
+
+fn get_sponsor_amount(e: &Env, voting: &Address, round_id: u32, sponsor: &Address) -> i128 {
+  get_entry(e, voting, StorageConst::Sponsor(round_id, sponsor.clone())).unwrap_or(0)
+}
+
+fn get_pledges(e: &Env, voting: &Address, round_id: u32) -> Vec<Pledge> {
+  let mut pledges: Vec<Pledge> = Vec::new(e);
+  for i in 0..get_round_counts(e, voting, round_id).pledges {
+      pledges.push_back(get_entry(e, voting, StorageConst::Pledge(round_id, i)).expect("not initialized yet"));
+  }
+  pledges
+}
+
+fn get_memo_count(e: &Env, voting: &Address, round_id: u32, association: &Address) -> u32 {
+  get_entry(e, voting, StorageConst::MemoCount(round_id, association.clone())).unwrap_or(0)
+}
+
+//Function to get the public memos among a page of the memos of an association, the private ones are left out.
+fn get_public_memos_page(e: &Env, voting: &Address, round_id: u32, association: &Address, offset: u32, limit: u32) -> Vec<DepositMemo> {
+  let (start, end) = get_page_range(get_memo_count(e, voting, round_id, association), offset, limit);
+  let mut memos: Vec<DepositMemo> = Vec::new(e);
+  for i in start..end {
+      if let Some(deposit_memo) = get_entry::<DepositMemo>(e, voting, StorageConst::Memo(round_id, association.clone(), i)) {
+          if deposit_memo.memo.public {
+              memos.push_back(deposit_memo);
+          }
+      }
+  }
+  memos
+}
+
+fn get_association_tokens(e: &Env, voting: &Address, round_id: u32, association: &Address) -> Map<Address, TokenDonation> {
+  get_entry(e, voting, StorageConst::AssociationTokens(round_id, association.clone())).unwrap_or(Map::new(e))
+}
+
+fn get_commitment(e: &Env, voting: &Address, round_id: u32, donor: &Address) -> Option<Commitment> {
+  get_entry(e, voting, StorageConst::Commitment(round_id, donor.clone()))
+}
+
+//The same check as is_attested of the voting contract.
+fn is_attested(e: &Env, voting: &Address, round_id: u32, donor: &Address) -> bool {
+  get_entry::<u64>(e, voting, StorageConst::Attested(round_id, donor.clone()))
+    .is_some_and(|expiry| expiry >= get_deadline(e, voting, round_id))
+}
+
+fn get_amount(e: &Env, voting: &Address, round_id: u32) -> i128 {
+  get_entry(e, voting, StorageConst::TotalAmount(round_id)).expect("not initialized yet")
+}
+
+fn get_matching_pool(e: &Env, voting: &Address, round_id: u32) -> i128 {
+  get_entry(e, voting, StorageConst::MatchingPool(round_id)).expect("not initialized yet")
+}
+
+fn get_matching_config(e: &Env, voting: &Address, round_id: u32) -> MatchingConfig {
+  get_entry(e, voting, StorageConst::MatchingConfig(round_id)).expect("not initialized yet")
+}
+
+fn get_verifier(e: &Env, voting: &Address, round_id: u32) -> Option<BytesN<32>> {
+  get_entry(e, voting, StorageConst::Verifier(round_id))
+}
+
+fn get_contribution_limits(e: &Env, voting: &Address, round_id: u32) -> Option<ContributionLimits> {
+  get_entry(e, voting, StorageConst::ContributionLimits(round_id))
+}
+
+fn get_commit_reveal(e: &Env, voting: &Address, round_id: u32) -> Option<CommitRevealConfig> {
+  get_entry(e, voting, StorageConst::CommitReveal(round_id))
+}
+
+fn get_token_config(e: &Env, voting: &Address, round_id: u32) -> Option<TokenConfig> {
+  get_entry(e, voting, StorageConst::TokenConfig(round_id))
+}
+
+#[contract]
+pub struct QueryContract;
+
+pub trait QueryTrait{
+  /*
+  Initialize the contract, it needs:
+  voting: the address of the voting contract to read the rounds from.
+  */
+  fn init(
+    env: Env,
+    voting: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by anyone to extend the time to live of the contract instance, with the thresholds of the voting contract.
+  fn extend_ttl(
+    env: Env,
+  );
+
+  //This function will be called by anyone to get the number of rounds, the round ids go from 0 to count - 1.
+  fn round_count(
+    env: Env
+  ) -> u32;
+
+  //This function will be called by anyone to get the associations addresses.
+  fn associations_addresses(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Address>, Error>;
+
+  /*
+  This function will be called by anyone to get the state of a round: 0 setup, 1 applications, 2 donations,
+  3 calculated, 4 review, 5 payout, 6 closed, 7 cancelled and 8 ended.
+  */
+  fn state(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u32, Error>;
+
+  //This function will be called by anyone to get the deadline of a round.
+  fn deadline(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u64, Error>;
+
+  //This function will be called by anyone to get the amounts per associations.
+  fn associations_amounts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Association>, Error>;
+
+  //This function will be calle by anyone to get the amount given by each donor to a certain association.
+  fn association_amount(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Vec<i128>, Error>;
+
+  //This function will be called by anyone to get the contribution of each donor to a certain association.
+  fn association_donors(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Map<Address, i128>, Error>;
+
+  fn total_amount(
+    env: Env,
+    round_id: u32,
+  ) -> Result<i128, Error>;
+
+  fn total_final_associations(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<FinalAssociation>, Error>;
+
+  /*
+  This function will be called by anyone to get up to limit associations of a round starting at offset,
+  with their number of donors. The limit is capped to 50, use count to know how many pages there are.
+  */
+  fn associations_amounts_page(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<AssociationSummary>, Error>;
+
+  //This function will be called by anyone to get the contribution of up to limit donors of an association starting at offset, the limit is capped to 50.
+  fn association_donors_page(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Map<Address, i128>, Error>;
+
+  //This function will be called by anyone to get up to limit calculated associations of a round starting at offset.
+  fn total_final_associations_page(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<FinalAssociation>, Error>;
+
+  //This function will be called by anyone to get the number of associations of a round.
+  fn count(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u32, Error>;
+
+  //This function will be called by anyone to get the amount of the matching pool.
+  fn matching_pool(
+    env: Env,
+    round_id: u32,
+  ) -> Result<i128, Error>;
+
+  //This function will be called by anyone to get the matching parameters of a round.
+  fn matching_config(
+    env: Env,
+    round_id: u32,
+  ) -> Result<MatchingConfig, Error>;
+
+  //This function will be called by anyone to get the amount each sponsor of a page of the sponsors put in the matching pool.
+  fn sponsors(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Map<Address, i128>, Error>;
+
+  //This function will be called by anyone to get the verifier of a round, None when every donation is counted for the matching.
+  fn verifier(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<BytesN<32>>, Error>;
+
+  //This function will be called by anyone to know if a donor is attested for a round, with an attestation valid at its deadline.
+  fn is_attested(
+    env: Env,
+    round_id: u32,
+    donor: Address,
+  ) -> Result<bool, Error>;
+
+  //This function will be called by anyone to get the contribution limits of a round, None when there are none.
+  fn contribution_limits(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<ContributionLimits>, Error>;
+
+  //This function will be called by anyone to get the pledges of the sponsors of a round and what they matched.
+  fn pledges(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Pledge>, Error>;
+
+  //This function will be called by anyone to get the commit-reveal config of a round, None when the deposits are public.
+  fn commit_reveal(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<CommitRevealConfig>, Error>;
+
+  //This function will be called by anyone to get the commitment of a donor that is not revealed or settled yet, or that was forfeited.
+  fn commitment(
+    env: Env,
+    round_id: u32,
+    donor: Address,
+  ) -> Result<Option<Commitment>, Error>;
+
+  /*
+  This function will be called by anyone to get the memos the donors of an association chose to make public,
+  among up to limit memos starting at offset (capped to 50). The private ones are left out so a page can have less,
+  use memo_count to know how many pages there are.
+  */
+  fn memos(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<DepositMemo>, Error>;
+
+  //This function will be called by anyone to get the number of memos, public and private, attached to the deposits to an association.
+  fn memo_count(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<u32, Error>;
+
+  //This function will be called by anyone to get the tokens accepted by a round besides its base token.
+  fn token_config(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<TokenConfig>, Error>;
+
+  //This function will be called by anyone to get the amount and the value an association received in each accepted token.
+  fn association_tokens(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Map<Address, TokenDonation>, Error>;
+
+  //This function will be called by anyone to get the thresholds used to extend the time to live of the entries.
+  fn ttl_config(
+    env: Env,
+  ) -> TtlConfig;
+
+  //This function will be called by anyone to get the distribution contract of a round, once it has been withdrawn.
+  fn distribution_address(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<Address>, Error>;
+
+  //This function will be called by anyone to get the number of sponsors, pledges and committers of a round.
+  fn round_counts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<RoundCounts, Error>;
+
+  //This function will be called by anyone to get the end of the challenge period of a round, 0 before end_funding.
+  fn review_deadline(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u64, Error>;
+}
+
+#[contractimpl]
+impl QueryTrait for QueryContract {
+  fn init(
+    env: Env,
+    voting: Address,
+  ) -> Result<(), Error> {
+    if env.storage().instance().has(&QueryConst::Voting) {
+      log!(
+        &env,
+        "Something went wrong, the contract is already initialized."
+      );
+      return Err(Error::AlreadyInitialized);
+    }
+    env.storage().instance().set(&QueryConst::Voting, &voting);
+    let config: TtlConfig = get_ttl_config(&env, &voting);
+    env.storage().instance().bump(config.low, config.high);
+    Ok(())
+  }
+
+  fn extend_ttl(
+    env: Env,
+  ) {
+    let config: TtlConfig = get_ttl_config(&env, &get_voting(&env));
+    env.storage().instance().bump(config.low, config.high);
+  }
+
+  fn round_count(
+    env: Env
+  ) -> u32 {
+    get_round_count(&env, &get_voting(&env))
+  }
+
+  fn associations_addresses(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Address>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_association_index(&env, &voting, round_id))
+  }
+
+  fn state(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u32, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_state(&env, &voting, round_id) as u32)
+  }
+
+  fn deadline(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u64, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_deadline(&env, &voting, round_id))
+  }
+
+  fn associations_amounts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Association>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_associations(&env, &voting, round_id))
+  }
+
+  fn association_amount(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Vec<i128>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    let contributions: Map<Address, i128> = get_contributions_per_association(&env, &voting, round_id, &association);
+    Ok(contributions.values())
+  }
+
+  fn association_donors(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Map<Address, i128>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_contributions_per_association(&env, &voting, round_id, &association))
+  }
+
+  fn total_amount(
+    env: Env,
+    round_id: u32,
+  ) -> Result<i128, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_amount(&env, &voting, round_id))
+  }
+
+  fn total_final_associations(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<FinalAssociation>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_final_associations(&env, &voting, round_id))
+  }
+
+  fn associations_amounts_page(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<AssociationSummary>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_associations_page(&env, &voting, round_id, offset, limit))
+  }
+
+  fn association_donors_page(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Map<Address, i128>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_donors_page(&env, &voting, round_id, &association, offset, limit))
+  }
+
+  fn total_final_associations_page(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<FinalAssociation>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    let (start, end) = get_page_range(get_final_association_count(&env, &voting, round_id), offset, limit);
+    let mut associations: Vec<FinalAssociation> = Vec::new(&env);
+    for i in start..end {
+      associations.push_back(get_final_association(&env, &voting, round_id, i));
+    }
+    Ok(associations)
+  }
+
+  fn count(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u32, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_association_index(&env, &voting, round_id).len())
+  }
+
+  fn matching_pool(
+    env: Env,
+    round_id: u32,
+  ) -> Result<i128, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_matching_pool(&env, &voting, round_id))
+  }
+
+  fn matching_config(
+    env: Env,
+    round_id: u32,
+  ) -> Result<MatchingConfig, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_matching_config(&env, &voting, round_id))
+  }
+
+  fn sponsors(
+    env: Env,
+    round_id: u32,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Map<Address, i128>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_sponsors_page(&env, &voting, round_id, offset, limit))
+  }
+
+  fn verifier(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<BytesN<32>>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_verifier(&env, &voting, round_id))
+  }
+
+  fn is_attested(
+    env: Env,
+    round_id: u32,
+    donor: Address,
+  ) -> Result<bool, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(is_attested(&env, &voting, round_id, &donor))
+  }
+
+  fn contribution_limits(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<ContributionLimits>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_contribution_limits(&env, &voting, round_id))
+  }
+
+  fn pledges(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Vec<Pledge>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_pledges(&env, &voting, round_id))
+  }
+
+  fn commit_reveal(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<CommitRevealConfig>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_commit_reveal(&env, &voting, round_id))
+  }
+
+  fn commitment(
+    env: Env,
+    round_id: u32,
+    donor: Address,
+  ) -> Result<Option<Commitment>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_commitment(&env, &voting, round_id, &donor))
+  }
+
+  fn memos(
+    env: Env,
+    round_id: u32,
+    association: Address,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<DepositMemo>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_public_memos_page(&env, &voting, round_id, &association, offset, limit))
+  }
+
+  fn memo_count(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<u32, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_memo_count(&env, &voting, round_id, &association))
+  }
+
+  fn token_config(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<TokenConfig>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_token_config(&env, &voting, round_id))
+  }
+
+  fn association_tokens(
+    env: Env,
+    round_id: u32,
+    association: Address,
+  ) -> Result<Map<Address, TokenDonation>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_association_tokens(&env, &voting, round_id, &association))
+  }
+
+  fn ttl_config(
+    env: Env,
+  ) -> TtlConfig {
+    get_ttl_config(&env, &get_voting(&env))
+  }
+
+  fn distribution_address(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Option<Address>, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_contract_call_address(&env, &voting, round_id))
+  }
+
+  fn round_counts(
+    env: Env,
+    round_id: u32,
+  ) -> Result<RoundCounts, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_round_counts(&env, &voting, round_id))
+  }
+
+  fn review_deadline(
+    env: Env,
+    round_id: u32,
+  ) -> Result<u64, Error> {
+    let voting: Address = get_voting(&env);
+    check_round_exists(&env, &voting, round_id)?;
+    Ok(get_review_deadline(&env, &voting, round_id))
+  }
+}
@@ -0,0 +1,543 @@
+#![no_std]
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+use soroban_sdk::{
+  contractclient, contracterror, contracttype, log, Address, BytesN, Env, IntoVal, String, TryFromVal, Val, Vec,
+};
+
+#[contracterror]
+#[derive(Clone, Debug, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error{
+  InvalidAuth = 1,
+  KeyExpected = 2,
+  ExpectedExtraValue = 3,
+  InvalidAmount = 4,
+  AlreadyWithdrawn = 5,
+  InvalidAssociation = 6,
+  InvalidTimestamp = 7,
+  AlreadyInitialized = 8,
+  InvalidPrecision = 9,
+  Overflow = 10,
+  InvalidRound = 11,
+  RoundCancelled = 12,
+  ApplicationsClosed = 13,
+  AlreadyApplied = 14,
+  InvalidApplication = 15,
+  InvalidMetadata = 16,
+  InvalidState = 17,
+  ChallengePeriod = 18,
+  UnresolvedChallenge = 19,
+  InvalidChallenge = 20,
+  DistributionNotSet = 21,
+  InvalidTtl = 22,
+  InvalidToken = 23,
+  InvalidPrice = 24,
+  InvalidCommitment = 25,
+  InvalidPledge = 26,
+  InvalidContribution = 27,
+  InvalidAttestation = 28,
+  InvalidRole = 29,
+  InvalidAdmin = 30,
+  PairsNotTallied = 31,
+  RefundsPending = 32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalAssociation{
+  pub name: Address,
+  pub contribution: i128,
+  pub direct: i128,
+  pub matched: i128,
+  pub capped: bool,
+  pub suspended: bool,
+  pub has_withdrawn: bool,
+}
+
+impl FinalAssociation{
+  pub fn get_matched(&self) -> i128 {
+    self.matched
+  }
+  // the total contribution is always the direct donations plus the matched amount
+  pub fn set_matched(&mut self, amount: i128){
+    self.matched = amount;
+    self.contribution = self.direct + amount;
+  }
+}
+
+/*
+The roles allowed to call the privileged functions, granted and revoked by the owners in the manager contract:
+Owner: everything, including granting and revoking the roles. The admin set on init is always an owner.
+Operator: runs the rounds, from creating them and managing their associations to approving the funding.
+Treasurer: withdraws the funds of the rounds.
+Reviewer: approves or rejects the applications and dismisses the challenges.
+*/
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    Owner = 0,
+    Operator = 1,
+    Treasurer = 2,
+    Reviewer = 3,
+}
+
+/*
+The keys of the voting contract, the manager and query contracts read them with its entry function (see get_entry).
+The associations of a round, their donations and every list that grows with the round are kept in persistent storage, one entry per key:
+AssociationIndex: the addresses of the associations, to iterate them.
+AssociationSuspended: whether an association is suspended, its presence means the association is part of the round.
+AssociationTotals: the running totals of the donations to an association, see AssociationTotals.
+DonorIndex: the addresses of the donors of an association, to iterate them, split in entries of DONOR_CHUNK_SIZE addresses.
+DonorCount: the number of donors of an association, it stays until the donations of a removed association are all refunded.
+Donations: the amount given by a donor to each association, in the base token of the round.
+TokenContribution: the amount given by a donor to an association in another accepted token, and its value in the base token.
+AssociationTokens: the same amounts added up per token for an association, to pay it out.
+Memo: the memo of a deposit to an association, by its position, along with their number (MemoCount).
+Committer: the donors who committed to a round in the commit-reveal mode by position, to settle the unrevealed commitments,
+their number is in Counts and a donor is taken out when it reveals.
+Commitment: the commitment of a donor, until it is revealed or settled, a forfeited commitment stays to be refunded if the round is cancelled.
+Forfeited: the part of the matching pool that comes from the forfeited commitments, it has no sponsor.
+Attested: the expiry of the attestation of the verifier presented by a donor, their donations count for the matching only while it is valid.
+PairTally: the pairwise weights tallied so far, see PairTally.
+FinalAssociation: the calculated funding of an association, by its position, along with their number (FinalAssociationCount).
+Sponsor and Pledge: the amount put in the matching pool by a sponsor, and the pledges by their position, their numbers are in Counts.
+SponsorIndex: the address of a sponsor by its position.
+Unmatched: the part of the matching pool returned to the sponsors after the withdraw, see UnmatchedPool.
+Everything else stays in instance storage.
+*/
+#[contracttype(export = false)]
+pub enum StorageConst {
+    Manager,
+    RoundCount,
+    AssociationIndex(u32),
+    AssociationSuspended(u32, Address),
+    DonorIndex(u32, Address, u32),
+    DonorCount(u32, Address),
+    Donations(u32, Address),
+    AssociationTotals(u32, Address),
+    TokenContribution(u32, Address, Address, Address),
+    AssociationTokens(u32, Address),
+    Memo(u32, Address, u32),
+    MemoCount(u32, Address),
+    Committer(u32, u32),
+    Commitment(u32, Address),
+    Forfeited(u32),
+    Attested(u32, Address),
+    PairTally(u32),
+    FinalAssociation(u32, u32),
+    FinalAssociationCount(u32),
+    Deadline(u32),
+    AssetAdress(u32),
+    State(u32),
+    TotalAmount(u32),
+    ContractCallAddress(u32),
+    MatchingPool(u32),
+    MatchingConfig(u32),
+    Sponsor(u32, Address),
+    SponsorIndex(u32, u32),
+    Unmatched(u32),
+    Counts(u32),
+    ApplicationDeadline(u32),
+    ReviewDeadline(u32),
+    DistributionWasm,
+    TtlConfig,
+    ChallengePeriod(u32),
+    TokenConfig(u32),
+    CommitReveal(u32),
+    ContributionLimits(u32),
+    Verifier(u32),
+    Pledge(u32, u32),
+}
+
+//Approximate number of ledgers closed in a day, one every 5 seconds.
+pub const LEDGERS_PER_DAY: u32 = 17280;
+
+//Default thresholds of the TtlConfig.
+pub const BUMP_LOW: u32 = 30 * LEDGERS_PER_DAY;
+pub const BUMP_HIGH: u32 = 90 * LEDGERS_PER_DAY;
+
+//Highest time to live an entry can be bumped to, the max_entry_expiration of the network (6312000) counts the current ledger.
+pub const MAX_TTL: u32 = 6_312_000 - 1;
+
+//Highest number of items returned by a page of the paginated queries.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+//Number of donor addresses kept in each entry of the DonorIndex, so a new donor only rewrites the last one.
+pub const DONOR_CHUNK_SIZE: u32 = 50;
+
+/*
+The thresholds used to extend the time to live of the entries: an entry with less than low ledgers
+left is bumped to live high ledgers. The instance is bumped by every call that changes it,
+the persistent entries when they are written, and both by extend_ttl.
+They are set in the voting contract, the manager contract bumps its own entries with the same thresholds.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+  pub low: u32,
+  pub high: u32,
+}
+
+/*
+The other tokens a round accepts besides its base token, set by an operator before the donations:
+oracle: the price oracle contract used to value the donations in the base token (see PriceOracleInterface).
+tokens: the accepted tokens, the base token can not be one of them.
+max_price_age: how many seconds old a price of the oracle can be to be used.
+The donations are added up by their value in the base token for the matching, and every association
+is paid out the donations it received in the token they were made, the matching in the base token.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenConfig {
+  pub oracle: Address,
+  pub tokens: Vec<Address>,
+  pub max_price_age: u64,
+}
+
+//An amount received in an accepted token and its value in the base token at the time of the donation.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenDonation {
+  pub amount: i128,
+  pub value: i128,
+}
+
+/*
+A short message or reference a donor attaches to a deposit (max 128 bytes).
+public: whether the memo is returned by the memos query and published in the deposit event,
+a private memo is still readable from the ledger like everything else.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Memo {
+  pub text: String,
+  pub public: bool,
+}
+
+//The memo of a deposit along with the donor who made it, kept for every deposit with a memo.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositMemo {
+  pub donor: Address,
+  pub memo: Memo,
+}
+
+/*
+The limits on the contributions of a round, set by an operator before the donations, in the base token
+(the value of the donations in the other tokens), 0 for no limit:
+min_deposit: the smallest amount of a single deposit (or of a single allocation of a commitment).
+max_per_donor: the most a donor can give to the whole round.
+max_per_association: the most a donor can give to a single association.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContributionLimits {
+  pub min_deposit: i128,
+  pub max_per_donor: i128,
+  pub max_per_association: i128,
+}
+
+/*
+The pledge of a sponsor to match the direct donations of a round (of the attested donors when it has a verifier),
+escrowed in the base token:
+multiplier_bps: how much is added per unit donated to an association, in basis points (10000 matches 1:1).
+budget: the most the sponsor gives, when the donations need more every match is scaled down in proportion.
+matched: what the pledge adds to the associations as of the last calculation of the funding,
+the rest of the budget is returned to the sponsor on withdraw.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pledge {
+  pub sponsor: Address,
+  pub multiplier_bps: u32,
+  pub budget: i128,
+  pub matched: i128,
+}
+
+/*
+The commit-reveal mode of a round, set by an operator before the donations so the allocations stay hidden until the deadline:
+reveal_deadline: until when the donors can reveal their commitments, after the deadline of the round.
+forfeit: whether the commitments not revealed in time go to the matching pool instead of back to their donors.
+While it is set the donors can only commit, the deposits are refused.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitRevealConfig {
+  pub reveal_deadline: u64,
+  pub forfeit: bool,
+}
+
+/*
+The funds a donor escrowed in the base token of a commit-reveal round, along with the hash of their allocation:
+sha256 of the XDR of the ScVal vector [contract, round_id, donor, allocations, salt], where contract is the address
+of the voting contract, allocations maps each association to the amount it gets (adding up to the amount)
+and salt is 32 random bytes kept by the donor until the reveal.
+position: the position of the donor among the committers of the round.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commitment {
+  pub hash: BytesN<32>,
+  pub amount: i128,
+  pub position: u32,
+}
+
+/*
+The number of entries of the lists of a round kept by position in persistent storage,
+settled being the committers settled so far by settle_commitments.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundCounts {
+  pub sponsors: u32,
+  pub pledges: u32,
+  pub committers: u32,
+  pub settled: u32,
+}
+
+/*
+The settings of a round an operator changes through the manager contract before the donations,
+which checks them and hands them to the configure function of the voting contract, kept there in an entry each,
+the optional ones are removed when they are not set:
+verifier: the ed25519 public key of the verifier, only the donations of the attested donors are counted for the matching when it is set.
+limits: the contribution limits, see ContributionLimits.
+commit_reveal: the commit-reveal mode, see CommitRevealConfig.
+tokens: the tokens accepted besides the base token, see TokenConfig.
+challenge_period: how many seconds the funding stays in review before the payout.
+*/
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundConfig {
+  pub verifier: Option<BytesN<32>>,
+  pub limits: Option<ContributionLimits>,
+  pub commit_reveal: Option<CommitRevealConfig>,
+  pub tokens: Option<TokenConfig>,
+  pub challenge_period: u64,
+}
+
+/*
+Version of the events, every event is published with the topics (name, EVENTS_VERSION) followed by
+the round id for the events of a round, so an indexer can tell apart the layouts of the data.
+The voting contract publishes:
+init: manager. dist_wasm: wasm_hash. ttl: ttl config.
+round: (token_address, deadline, config). state: the new State.
+assoc_add: association. assoc_rm: (association, donors to refund). suspend: (association, suspended). apps_open: application_deadline.
+deposit: (donor, association, amount, public memo). dep_token: (donor, association, token, amount, value, public memo).
+matching: (sponsor, amount). refund: (donor, amount).
+pledge: (sponsor, multiplier_bps, budget). unpledged: (sponsor, returned). attested: (donor, expiry).
+commit: (donor, committed amount). reveal: (donor, allocations, returned). unreveal: (donor, amount, forfeited).
+calculate: (total_amount, matching_pool). withdraw: (distribution contract, amount). unmatched: (sponsor or admin when there is no sponsor, returned).
+The manager contract publishes:
+init: admin. grant: (role, address). revoke: (role, address).
+propose: (admin, new_admin). accept: (old_admin, admin). unpropose: new_admin.
+verifier: the ed25519 public key. limits: contribution limits. commit_rv: commit-reveal config. tokens: token config.
+chal_time: challenge_period. apply: (association, metadata). reviewed: (association, status).
+challenge: (challenger, reason). resolved: (challenger, upheld).
+Version 2 added the memo to deposit and dep_token, it is None when the memo is private.
+Version 3 changed assoc_rm to the number of donors to refund, the refunds follow with refund_association.
+Version 4 moved the events of the roles, the settings of the rounds, the applications and the challenges to the manager contract,
+and changed the init of the voting contract to its manager.
+*/
+pub const EVENTS_VERSION: u32 = 4;
+
+/*
+The way the matching pool is split between the associations:
+Quadratic: plain quadratic funding, (sum of sqrt(donor contribution))^2.
+Pairwise: pairwise-bounded coordination subsidies, the match of every pair of donors is
+dampened by how much that same pair co-funds across all the associations.
+*/
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MatchingMode {
+    Quadratic = 0,
+    Pairwise = 1,
+}
+
+/*
+The parameters of the matching calculation, set at init:
+precision: number of decimal digits used by the quadratic funding calculations (from 1 to 9).
+mode: the way the matching pool is split between the associations.
+pairwise_threshold: the coordination amount at which the match of a pair of donors is halved,
+only used (and required to be positive) by the pairwise mode.
+cap: the maximum amount of matching a single association can receive, 0 for no cap.
+cap_bps: the same maximum in basis points of the matching pool, 0 for no cap.
+When both caps are set the lowest one is applied.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchingConfig {
+  pub precision: u32,
+  pub mode: MatchingMode,
+  pub pairwise_threshold: i128,
+  pub cap: i128,
+  pub cap_bps: u32,
+}
+
+/*
+The lifecycle of a round, every endpoint checks the state it is allowed in:
+Setup: the round is created, the operators can add associations and the sponsors fund the matching pool.
+Applications: the associations can apply and the reviewers approve or reject them.
+Donations: the donors can deposit until the deadline, the funding can only be calculated after it.
+Calculated: the funding has been calculated, it can be calculated again.
+Review: the funding is frozen during the challenge period, the associations and the donors can challenge it.
+Payout: the funding is approved and the funds can be withdrawn.
+Closed: the funds have been withdrawn.
+Cancelled: the round has been aborted, the deposits can only be refunded.
+Ended: the donations are over but the funding has to be calculated again, the round goes back there
+when its associations change after a calculation.
+*/
+#[contracttype(export = false)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum State {
+    Setup = 0,
+    Applications = 1,
+    Donations = 2,
+    Calculated = 3,
+    Review = 4,
+    Payout = 5,
+    Closed = 6,
+    Cancelled = 7,
+    Ended = 8,
+}
+
+//Function to get the bounds of the page starting at offset, the limit is capped to MAX_PAGE_SIZE.
+pub fn get_page_range(len: u32, offset: u32, limit: u32) -> (u32, u32) {
+  let start = offset.min(len);
+  let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+  (start, end)
+}
+
+/*
+The interface of the voting contract (deployer-contract) used by the manager contract, which initializes it
+and hands it the settings of the rounds, and by the query contract, which only reads its entries.
+*/
+#[contractclient(name = "VotingClient")]
+pub trait VotingInterface {
+  fn init(
+    env: Env,
+    manager: Address,
+  );
+
+  fn add_association(
+    env: Env,
+    admin: Address,
+    round_id: u32,
+    association: Address,
+  );
+
+  fn configure(
+    env: Env,
+    round_id: u32,
+    verifier: Option<BytesN<32>>,
+    limits: Option<ContributionLimits>,
+    commit_reveal: Option<CommitRevealConfig>,
+    tokens: Option<TokenConfig>,
+    challenge_period: u64,
+  );
+
+  fn reset_funding(
+    env: Env,
+    round_id: u32,
+  );
+
+  fn entry(
+    env: Env,
+    key: Val,
+  ) -> Option<Val>;
+}
+
+//Function to read an entry of the voting contract with its entry function, None when it does not exist.
+pub fn get_entry<V: TryFromVal<Env, Val>>(e: &Env, voting: &Address, key: StorageConst) -> Option<V> {
+  VotingClient::new(e, voting)
+    .entry(&key.into_val(e))
+    .and_then(|value| V::try_from_val(e, &value).ok())
+}
+
+pub fn get_round_count(e: &Env, voting: &Address) -> u32 {
+  get_entry(e, voting, StorageConst::RoundCount).expect("not initialized yet")
+}
+
+pub fn get_state(e: &Env, voting: &Address, round_id: u32) -> State {
+  get_entry(e, voting, StorageConst::State(round_id)).expect("not initialized yet")
+}
+
+pub fn get_deadline(e: &Env, voting: &Address, round_id: u32) -> u64 {
+  get_entry(e, voting, StorageConst::Deadline(round_id)).expect("not initialized yet")
+}
+
+//The applications of a round are closed until an operator opens them.
+pub fn get_application_deadline(e: &Env, voting: &Address, round_id: u32) -> Option<u64> {
+  get_entry(e, voting, StorageConst::ApplicationDeadline(round_id))
+}
+
+//0 until the voting contract sets it with end_funding.
+pub fn get_review_deadline(e: &Env, voting: &Address, round_id: u32) -> u64 {
+  get_entry(e, voting, StorageConst::ReviewDeadline(round_id)).unwrap_or(0)
+}
+
+pub fn get_token_address(e: &Env, voting: &Address, round_id: u32) -> Address {
+  get_entry(e, voting, StorageConst::AssetAdress(round_id)).expect("not initialized yet")
+}
+
+pub fn get_round_config(e: &Env, voting: &Address, round_id: u32) -> RoundConfig {
+  RoundConfig {
+    verifier: get_entry(e, voting, StorageConst::Verifier(round_id)),
+    limits: get_entry(e, voting, StorageConst::ContributionLimits(round_id)),
+    commit_reveal: get_entry(e, voting, StorageConst::CommitReveal(round_id)),
+    tokens: get_entry(e, voting, StorageConst::TokenConfig(round_id)),
+    challenge_period: get_entry(e, voting, StorageConst::ChallengePeriod(round_id)).expect("not initialized yet"),
+  }
+}
+
+//The thresholds of the voting contract, its default ones until an owner sets them there.
+pub fn get_ttl_config(e: &Env, voting: &Address) -> TtlConfig {
+  get_entry(e, voting, StorageConst::TtlConfig).unwrap_or(TtlConfig { low: BUMP_LOW, high: BUMP_HIGH })
+}
+
+pub fn is_association(e: &Env, voting: &Address, round_id: u32, association: &Address) -> bool {
+  get_entry::<bool>(e, voting, StorageConst::AssociationSuspended(round_id, association.clone())).is_some()
+}
+
+pub fn is_donor(e: &Env, voting: &Address, round_id: u32, donor: &Address) -> bool {
+  get_entry::<Val>(e, voting, StorageConst::Donations(round_id, donor.clone())).is_some()
+}
+
+//Function to check that a round of the voting contract exists, the queries also answer for a cancelled round.
+pub fn check_round_exists(e: &Env, voting: &Address, round_id: u32) -> Result<(), Error> {
+  if round_id >= get_round_count(e, voting) {
+    log!(
+      e,
+      "Something went wrong, the round does not exist."
+    );
+    return Err(Error::InvalidRound);
+  }
+  Ok(())
+}
+
+pub fn check_round(e: &Env, voting: &Address, round_id: u32) -> Result<(), Error> {
+  check_round_exists(e, voting, round_id)?;
+  if get_state(e, voting, round_id) == State::Cancelled {
+    log!(
+      e,
+      "Something went wrong, the round has been cancelled."
+    );
+    return Err(Error::RoundCancelled);
+  }
+  Ok(())
+}
+
+//Function to check that a round of the voting contract is in one of the states the endpoint is allowed in.
+pub fn check_state(e: &Env, voting: &Address, round_id: u32, states: &[State]) -> Result<(), Error> {
+  if !states.contains(&get_state(e, voting, round_id)) {
+    log!(
+      e,
+      "Something went wrong, the round is not in the right state for this call."
+    );
+    return Err(Error::InvalidState);
+  }
+  Ok(())
+}
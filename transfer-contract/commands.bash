@@ -27,7 +27,7 @@ soroban contract invoke --id ___ --source cashabroad --network testnet -- init -
 soroban contract invoke --id ___ --source cashabroad --network testnet -- add_association --admin GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK --association GAFIA4AE62FMOZ642IKVSQUM3XF7AREWNOBNIY4FTNBKTJPPJIKKLVUV
 
 ## Invoke the deposit function.}
-soroban contract invoke --id ____ --source cashabroad --network testnet -- deposit --association  GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --amount 200000000
+soroban contract invoke --id ____ --source cashabroad --network testnet -- deposit --from GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --association  GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK --amount 200000000
 
 ## Invoke the withdraw function.
 soroban contract invoke --id ___ --source cashabroad --network testnet -- withdraw --admin GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK 
@@ -39,4 +39,48 @@ soroban contract invoke --id ___ --source cashabroad --network testnet -- reset_
 soroban contract invoke --id ___ --source cashabroad --network testnet -- get_associations 
 
 ## Invoke the get total function.
-soroban contract invoke --id ___ --source cashabroad --network testnet -- total
\ No newline at end of file
+soroban contract invoke --id ___ --source cashabroad --network testnet -- total
+
+## Invoke the remove association function.
+soroban contract invoke --id ___ --source cashabroad --network testnet -- remove_association --association GAFIA4AE62FMOZ642IKVSQUM3XF7AREWNOBNIY4FTNBKTJPPJIKKLVUV --admin GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK
+
+## Invoke the suspend association function (use --suspended false to resume it).
+soroban contract invoke --id ___ --source cashabroad --network testnet -- suspend_association --association GAFIA4AE62FMOZ642IKVSQUM3XF7AREWNOBNIY4FTNBKTJPPJIKKLVUV --admin GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK --suspended true
+
+## Replace the associations with the calculated funding, it is called by the withdraw of the voting contract (the distributor) along with the funds
+soroban contract invoke --id ___ --source cashabroad --network testnet -- distribute --distributor GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK --associations '[ { "name": "GBXKU3C7KKKBJAH6FCXV6BXZ4ZZXUWP37XM3HNS32FXM2LCRQV4HLUBK", "contribution": "1200", "direct": "1000", "matched": "200", "capped": false, "suspended": false, "has_withdrawn": false } ]'
+
+
+## Set how many ledgers the storage lives, every call bumps the entries back up to high once they drop below low
+soroban contract invoke --id ___ --source cashabroad --network testnet -- set_ttl_config --admin GCFHD6NQ7DB75I4T2LQPUT2W336KRUMZYVOYIHIVF63IBUL4EZRNYOZK --config '{ "low": 518400, "high": 1555200 }'
+
+## Bump the storage of the contract, anyone can call it, one page of 50 associations at a time
+soroban contract invoke --id ___ --source cashabroad --network testnet -- extend_ttl --offset 0 --limit 50
+
+## Get the ttl config
+soroban contract invoke --id ___ --source cashabroad --network testnet -- ttl_config
+
+## Get the associations page by page (at most 50 per page), count returns how many there are
+soroban contract invoke --id ___ --source cashabroad --network testnet -- count
+soroban contract invoke --id ___ --source cashabroad --network testnet -- associations_page --offset 0 --limit 50
+
+## Grant a role to an address: 0 owner, 1 operator, 2 treasurer
+soroban contract invoke --id ___ --source cashabroad --network testnet -- grant_role --owner cashabroad --role 2 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Revoke a role
+soroban contract invoke --id ___ --source cashabroad --network testnet -- revoke_role --owner cashabroad --role 2 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Get the addresses granted a role and check if an address has it
+soroban contract invoke --id ___ --source cashabroad --network testnet -- role_members --role 2
+soroban contract invoke --id ___ --source cashabroad --network testnet -- has_role --role 2 --address GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Hand over the admin in two steps: the admin proposes the new one, which accepts with its own key
+soroban contract invoke --id ___ --source cashabroad --network testnet -- propose_admin --admin cashabroad --new_admin GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+soroban contract invoke --id ___ --source newadmin --network testnet -- accept_admin --new_admin GDZ5JQVEAGFL5RBJ2KJ4XGLT5MPGSLUN4S7MVUHXIIRCR67WOGNDG6SN
+
+## Or cancel the proposal before it is accepted
+soroban contract invoke --id ___ --source cashabroad --network testnet -- cancel_admin --admin cashabroad
+
+## Get the admin and the proposed one
+soroban contract invoke --id ___ --source cashabroad --network testnet -- admin
+soroban contract invoke --id ___ --source cashabroad --network testnet -- pending_admin
\ No newline at end of file
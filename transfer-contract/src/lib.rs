@@ -1,8 +1,11 @@
 #![no_std]
 
+#[cfg(test)]
+extern crate std;
+
 use soroban_sdk::{
-  contract, contracterror, contractimpl, contracttype, token, log,
-  Address, Env, Vec,
+  contract, contracterror, contractimpl, contracttype, token, log, symbol_short,
+  Address, Env, Vec, IntoVal, Val,
 };
 
 #[contracterror]
@@ -16,13 +19,25 @@ pub enum Error{
   InvalidTimestamp = 5,
   InvalidAssociation = 6,
   AlreadyInitialized = 7,
+  AlreadyDistributed = 8,
+  InvalidTtl = 9,
+  InvalidRole = 10,
+  InvalidAdmin = 11,
 }
 
+/*
+The same fields as the FinalAssociation of the voting contract, so the list can be sent by its withdraw.
+direct, matched and capped are the breakdown computed by the voting contract, only the contribution is paid out.
+*/
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FinalAssociation{
   name: Address,
   contribution: i128,
+  direct: i128,
+  matched: i128,
+  capped: bool,
+  suspended: bool,
   has_withdrawn: bool,
 }
 
@@ -45,81 +60,263 @@ impl FinalAssociation{
   }
 }
 
+/*
+The roles allowed to call the privileged functions, numbered like the ones of the voting contract
+so it can grant them when it deploys this contract, 3 is its Reviewer and stays unused here:
+Owner: everything, including granting and revoking the roles. The admin is always an owner.
+Operator: manages the associations and the withdraw deadline.
+Treasurer: triggers the monthly withdraw.
+*/
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+  Owner = 0,
+  Operator = 1,
+  Treasurer = 2,
+}
+
+/*
+The associations are kept in persistent storage, one entry per association (Association) along with
+the index of their addresses (AssociationIndex) to iterate them and the amount paid to each of them
+by the monthly withdraw (Paid). Everything else stays in instance storage, along with the address that
+initialized the contract (Distributor), the only one that can distribute, and whether it did (Distributed).
+*/
 #[contracttype]
 pub enum StorageConst {
   AdminAddress,
-  FinalAssociations,
+  PendingAdmin,
+  Distributor,
+  Distributed,
+  AssociationIndex,
+  Association(Address),
+  Paid(Address),
   Deadline,
   RecipientsClaimed,
   AssetAdress,
   ClaimMonth,
   TotalAmount,
   DeadlineWithdraw,
+  TtlConfig,
+  Roles(Role),
+}
+
+/*
+Version of the events, every event is published with the topics (name, EVENTS_VERSION),
+so an indexer can tell apart the layouts of the data:
+init: (admin, token_address, deadline). deposit: (from, association, amount). distrib: (associations, total_amount).
+assoc_add: association. assoc_rm: (association, contribution). suspend: (association, suspended).
+payout: (claim_month, paid). reset: the new withdraw deadline. grant: (role, address). revoke: (role, address).
+propose, accept and unpropose: the handover of the admin, with the data of the same events of the manager contract.
+*/
+const EVENTS_VERSION: u32 = 2;
+
+/*
+The same limits as the voting contract, they are documented in the shared crate.
+This contract does not depend on it, the types of the shared crate would end up in its spec.
+*/
+const LEDGERS_PER_DAY: u32 = 17280;
+const BUMP_LOW: u32 = 30 * LEDGERS_PER_DAY;
+const BUMP_HIGH: u32 = 90 * LEDGERS_PER_DAY;
+const MAX_TTL: u32 = 6_312_000 - 1;
+const MAX_PAGE_SIZE: u32 = 50;
+
+/*
+The thresholds used to extend the time to live of the entries, like the TtlConfig of the shared crate.
+Here the associations are bumped when they are written, and along with the instance by extend_ttl.
+*/
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+  pub low: u32,
+  pub high: u32,
+}
+
+fn set_persistent<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(e: &Env, key: &K, value: &V) {
+  let config: TtlConfig = get_ttl_config(e);
+  e.storage().persistent().set(key, value);
+  e.storage().persistent().bump(key, config.low, config.high);
+}
+
+fn bump_instance(e: &Env) {
+  let config: TtlConfig = get_ttl_config(e);
+  e.storage().instance().bump(config.low, config.high);
+}
+
+//Function to bump the index of the associations and a page of the associations.
+fn bump_associations(e: &Env, offset: u32, limit: u32) {
+  let config: TtlConfig = get_ttl_config(e);
+  if !e.storage().persistent().has(&StorageConst::AssociationIndex) {
+    return;
+  }
+  e.storage().persistent().bump(&StorageConst::AssociationIndex, config.low, config.high);
+  let index: Vec<Address> = get_association_index(e);
+  let (start, end) = get_page_range(index.len(), offset, limit);
+  for name in index.slice(start..end).iter() {
+      for key in [StorageConst::Association(name.clone()), StorageConst::Paid(name)] {
+        if e.storage().persistent().has(&key) {
+          e.storage().persistent().bump(&key, config.low, config.high);
+        }
+      }
+  }
 }
 
-fn init_associations(e: &Env, associations: Vec<Address>) -> Vec<FinalAssociation>{
-  let mut associations_vec: Vec<FinalAssociation> = Vec::new(&e);
+fn get_ttl_config(e: &Env) -> TtlConfig {
+  e.storage()
+      .instance()
+      .get::<_, TtlConfig>(&StorageConst::TtlConfig)
+      .unwrap_or(TtlConfig { low: BUMP_LOW, high: BUMP_HIGH })
+}
 
+fn init_associations(e: &Env, associations: &Vec<Address>) {
+  set_persistent(e, &StorageConst::AssociationIndex, &Vec::<Address>::new(e));
   for association in associations.iter() {
-      add_new_association(&mut associations_vec, &association);
+      add_new_association(e, &association);
   }
-  associations_vec
 }
 
-fn add_new_association(associations: &mut Vec<FinalAssociation>, name: &Address) {
+//Function to add an association, nothing changes if it is already part of the contract.
+fn add_new_association(e: &Env, name: &Address) {
+  if e.storage().persistent().has(&StorageConst::Association(name.clone())) {
+      return;
+  }
   let new_assoc: FinalAssociation = FinalAssociation {
       name: name.clone(),
       contribution: 0,
+      direct: 0,
+      matched: 0,
+      capped: false,
+      suspended: false,
       has_withdrawn: false,
   };
-  associations.push_back(new_assoc);
-}
-
-fn add_contribution(associations: &mut Vec<FinalAssociation>, association: &Address, amount: i64) -> Vec<FinalAssociation>{
-  let amount_i128: i128 = amount as i128;
-  let mut i:u32 = 0;
-  for mut assoc in associations.iter() {
-      if assoc.get_name() == association {
-          let amount_assoc = assoc.get_contribution();
-          let new_amount = amount_assoc + amount_i128;
-          assoc.set_contribution(new_amount);
-          associations.set(i, assoc);
+  let mut index: Vec<Address> = get_association_index(e);
+  index.push_back(name.clone());
+  set_persistent(e, &StorageConst::AssociationIndex, &index);
+  set_association(e, &new_assoc);
+}
+
+fn set_association(e: &Env, association: &FinalAssociation) {
+  set_persistent(e, &StorageConst::Association(association.get_name().clone()), association);
+}
+
+fn add_contribution(e: &Env, association: &Address, amount: i64) -> Result<(), Error> {
+  if let Some(mut assoc) = get_association(e, association) {
+      let new_amount = assoc.get_contribution() + amount as i128;
+      assoc.set_contribution(new_amount);
+      set_association(e, &assoc);
+      return Ok(());
+  }
+  log!(
+    e,
+    "Something went wrong, the association is not part of the contract."
+  );
+  Err(Error::InvalidAssociation)
+}
+
+/*
+Function to remove an association, the part of its contribution that has not been paid yet
+is sent back to the admin. It returns the whole contribution of the association.
+*/
+fn remove_association(e: &Env, association: &Address) -> Result<i128, Error> {
+  if let Some(assoc) = get_association(e, association) {
+      let contribution: i128 = *assoc.get_contribution();
+      let remaining: i128 = contribution - get_paid(e, association);
+      if remaining > 0 {
+        transfer(e, &e.current_contract_address(), &get_admin_address(e), &remaining);
+      }
+      let mut index: Vec<Address> = get_association_index(e);
+      if let Some(i) = index.first_index_of(association) {
+          index.remove(i);
+      }
+      set_persistent(e, &StorageConst::AssociationIndex, &index);
+      e.storage().persistent().remove(&StorageConst::Association(association.clone()));
+      e.storage().persistent().remove(&StorageConst::Paid(association.clone()));
+      return Ok(contribution);
+  }
+  log!(
+    e,
+    "Something went wrong, the association is not part of the contract."
+  );
+  Err(Error::InvalidAssociation)
+}
+
+fn set_association_suspended(e: &Env, association: &Address, suspended: bool) -> Result<(), Error> {
+  if let Some(mut assoc) = get_association(e, association) {
+      assoc.suspended = suspended;
+      set_association(e, &assoc);
+      return Ok(());
+  }
+  log!(
+    e,
+    "Something went wrong, the association is not part of the contract."
+  );
+  Err(Error::InvalidAssociation)
+}
+
+//Function to replace every association with the ones sent by the voting contract.
+fn replace_associations(e: &Env, associations: &Vec<FinalAssociation>) {
+  for name in get_association_index(e).iter() {
+      e.storage().persistent().remove(&StorageConst::Association(name));
+  }
+  let mut index: Vec<Address> = Vec::new(e);
+  for association in associations.iter() {
+      index.push_back(association.get_name().clone());
+      set_association(e, &association);
+  }
+  set_persistent(e, &StorageConst::AssociationIndex, &index);
+}
+
+//Function to add what was deposited to each association before the distribution to its calculated contribution.
+fn add_deposits(e: &Env, associations: &Vec<FinalAssociation>) -> Vec<FinalAssociation> {
+  let mut merged: Vec<FinalAssociation> = Vec::new(e);
+  for mut association in associations.iter() {
+      if let Some(deposited) = get_association(e, association.get_name()) {
+        let contribution: i128 = association.get_contribution() + deposited.get_contribution();
+        association.set_contribution(contribution);
       }
-      i += 1;
+      merged.push_back(association);
   }
-  associations.clone()
+  merged
 }
 
-fn withdraw(e: &Env, associations: &mut Vec<FinalAssociation>) {
-  let claim_month = get_claim_month(&e);
+fn get_total_contribution(associations: &Vec<FinalAssociation>) -> i128 {
+  let mut total: i128 = 0;
+  for association in associations.iter() {
+      total += *association.get_contribution();
+  }
+  total
+}
+
+//Function to pay the monthly part of every association, it returns the amount paid.
+fn withdraw(e: &Env) -> i128 {
+  let claim_month = get_claim_month(e);
+  let mut paid: i128 = 0;
   if claim_month == 12{
-    return;
+    return paid;
   }
-  for mut association in associations.iter(){
+  for mut association in get_associations(e).iter(){
     let has_withdrawn: bool = *association.get_has_withdrawn();
-    if has_withdrawn == true {
+    if has_withdrawn || association.suspended {
       continue;
     }
-    let mut total_amount: i128;
-    total_amount  = *association.get_contribution() as i128;
-    total_amount = total_amount / 12;
+    let total_amount: i128 = *association.get_contribution() / 12;
     transfer(e, &e.current_contract_address(), association.get_name(), &total_amount);
     association.set_has_withdrawn(true);
+    set_association(e, &association);
+    set_persistent(e, &StorageConst::Paid(association.get_name().clone()), &(get_paid(e, association.get_name()) + total_amount));
+    paid += total_amount;
   }
-
+  paid
 }
 
-fn reset_withdraw(associations: &mut Vec<FinalAssociation>) -> Vec<FinalAssociation> {
-  let mut i = 0;
-  for mut association in associations.iter(){
+fn reset_withdraw(e: &Env) {
+  for mut association in get_associations(e).iter(){
     let has_withdrawn: bool = *association.get_has_withdrawn();
-    if has_withdrawn == true {
+    if has_withdrawn {
       association.set_has_withdrawn(false);
-      associations.set(i, association);
+      set_association(e, &association);
     }
-    i += 1;
   }
-  associations.clone()
 }
 
 fn transfer(e: &Env, from: &Address, to: &Address, amount: &i128) {
@@ -135,6 +332,52 @@ fn get_admin_address(e: &Env) -> Address {
       .expect("not initialized yet")
 }
 
+fn get_distributor(e: &Env) -> Address {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&StorageConst::Distributor)
+      .expect("not initialized yet")
+}
+
+fn get_distributed(e: &Env) -> bool {
+  e.storage()
+      .instance()
+      .get::<_, bool>(&StorageConst::Distributed)
+      .unwrap_or(false)
+}
+
+fn get_pending_admin(e: &Env) -> Option<Address> {
+  e.storage()
+      .instance()
+      .get::<_, Address>(&StorageConst::PendingAdmin)
+}
+
+fn get_role_members(e: &Env, role: Role) -> Vec<Address> {
+  e.storage()
+      .instance()
+      .get::<_, Vec<Address>>(&StorageConst::Roles(role))
+      .unwrap_or(Vec::new(e))
+}
+
+//The roles work like the ones of the manager contract, see its has_role.
+fn has_role(e: &Env, address: &Address, role: Role) -> bool {
+  if get_admin_address(e) == *address || get_role_members(e, Role::Owner).contains(address) {
+    return true;
+  }
+  get_role_members(e, role).contains(address)
+}
+
+fn check_role(e: &Env, address: &Address, role: Role) -> Result<(), Error> {
+  if !has_role(e, address, role) {
+    log!(
+      e,
+      "Something went wrong, the address does not have the role needed to call the function."
+    );
+    return Err(Error::InvalidAuth);
+  }
+  Ok(())
+}
+
 fn get_token_address(e: &Env) -> Address {
   e.storage()
       .instance()
@@ -146,13 +389,58 @@ fn get_ledger_timestamp(e: &Env) -> u64 {
   e.ledger().timestamp()
 }
 
-fn get_associations(e: &Env) -> Vec<FinalAssociation> {
+fn get_association_index(e: &Env) -> Vec<Address> {
   e.storage()
-    .instance()
-    .get::<_, Vec<FinalAssociation>>(&StorageConst::FinalAssociations)
+    .persistent()
+    .get::<_, Vec<Address>>(&StorageConst::AssociationIndex)
     .expect("not initialized yet")
 }
 
+fn get_association(e: &Env, association: &Address) -> Option<FinalAssociation> {
+  e.storage()
+    .persistent()
+    .get::<_, FinalAssociation>(&StorageConst::Association(association.clone()))
+}
+
+//Function to put together every association, for the monthly payouts and the queries.
+fn get_associations(e: &Env) -> Vec<FinalAssociation> {
+  let mut associations: Vec<FinalAssociation> = Vec::new(e);
+  for name in get_association_index(e).iter() {
+      if let Some(association) = get_association(e, &name) {
+          associations.push_back(association);
+      }
+  }
+  associations
+}
+
+//Function to put together a page of the associations, so the query only reads the entries it returns.
+fn get_associations_page(e: &Env, offset: u32, limit: u32) -> Vec<FinalAssociation> {
+  let index: Vec<Address> = get_association_index(e);
+  let (start, end) = get_page_range(index.len(), offset, limit);
+  let mut associations: Vec<FinalAssociation> = Vec::new(e);
+  for name in index.slice(start..end).iter() {
+      if let Some(association) = get_association(e, &name) {
+          associations.push_back(association);
+      }
+  }
+  associations
+}
+
+//Function to get the bounds of a page, see get_page_range in the shared crate.
+fn get_page_range(len: u32, offset: u32, limit: u32) -> (u32, u32) {
+  let start = offset.min(len);
+  let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+  (start, end)
+}
+
+//The amount paid to an association so far, the months it was suspended are not paid.
+fn get_paid(e: &Env, association: &Address) -> i128 {
+  e.storage()
+    .persistent()
+    .get::<_, i128>(&StorageConst::Paid(association.clone()))
+    .unwrap_or(0)
+}
+
 fn get_deadline(e: &Env) -> u64 {
   e.storage()
       .instance()
@@ -174,13 +462,6 @@ fn get_recipients_claimed(e: &Env) -> bool {
       .expect("not initialized yet")
 }
 
-fn get_final_associations(e: &Env) -> Vec<FinalAssociation> {
-  e.storage()
-    .instance()
-    .get::<_, Vec<FinalAssociation>>(&StorageConst::FinalAssociations)
-    .expect("not initialized yet")
-}
-
 fn get_deadline_withdraw(e: &Env) -> u64 {
   e.storage()
       .instance()
@@ -213,12 +494,50 @@ pub trait DistributionTrait{
     admin: Address,
   ) -> Result<(), Error>;
 
+  /*
+  This function will be called by an operator to remove an association,
+  the part of its contribution that has not been paid yet is sent back to the admin.
+  */
+  fn remove_association(
+    env: Env,
+    association: Address,
+    admin: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by an operator to suspend (or resume with suspended = false) an association,
+  a suspended association is skipped by the monthly withdraw.
+  */
+  fn suspend_association(
+    env: Env,
+    association: Address,
+    admin: Address,
+    suspended: bool,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by anyone to send funds to an association before the distribution, the amount is
+  transferred from the caller and added to the contribution of the association when the funding is distributed.
+  */
   fn deposit(
     env: Env,
+    from: Address,
     association: Address,
     amount: i64,
   ) -> Result<(), Error>;
 
+  /*
+  This function will be called by the withdraw of the voting contract that initialized this contract, after sending the funds,
+  to replace the associations with the calculated funding, the deposits made before are added to their contributions.
+  It can only be called once, before the monthly payouts. The balance of the contract has to cover the contributions,
+  anything above them (tokens sent directly to the contract) is sent back to the admin.
+  */
+  fn distribute(
+    env: Env,
+    distributor: Address,
+    associations: Vec<FinalAssociation>,
+  ) -> Result<(), Error>;
+
   fn reset_deadline(
     env: Env,
     admin: Address,
@@ -229,10 +548,104 @@ pub trait DistributionTrait{
     admin: Address,
   ) -> Result<(), Error>;
 
+  // This function will be called by an owner to set the TtlConfig of this contract.
+  fn set_ttl_config(
+    env: Env,
+    admin: Address,
+    config: TtlConfig,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an owner to grant a role to an address, see Role.
+  fn grant_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by an owner to revoke a role, like the revoke_role of the manager contract.
+  fn revoke_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>;
+
+  /*
+  This function will be called by the admin to propose a new admin, it is handed over in two steps
+  like the admin of the manager contract, see its propose_admin, accept_admin and cancel_admin.
+  */
+  fn propose_admin(
+    env: Env,
+    admin: Address,
+    new_admin: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by the proposed admin to become the admin.
+  fn accept_admin(
+    env: Env,
+    new_admin: Address,
+  ) -> Result<(), Error>;
+
+  // This function will be called by the admin to cancel its proposal.
+  fn cancel_admin(
+    env: Env,
+    admin: Address,
+  ) -> Result<(), Error>;
+
+  //This function will be called by anyone to get the admin, which receives what a removed association was not paid.
+  fn admin(
+    env: Env,
+  ) -> Address;
+
+  //This function will be called by anyone to get the proposed admin.
+  fn pending_admin(
+    env: Env,
+  ) -> Option<Address>;
+
+  //This function will be called by anyone to get the addresses granted a role.
+  fn role_members(
+    env: Env,
+    role: Role,
+  ) -> Vec<Address>;
+
+  //This function will be called by anyone to know if an address has a role, the admin and the owners have them all.
+  fn has_role(
+    env: Env,
+    role: Role,
+    address: Address,
+  ) -> bool;
+
+  /*
+  This function will be called by anyone to extend the time to live of the contract instance and of up to limit associations
+  starting at offset (capped to 50), so they do not expire during the 12 months of payouts.
+  */
+  fn extend_ttl(
+    env: Env,
+    offset: u32,
+    limit: u32,
+  );
+
+  fn ttl_config(
+    env: Env,
+  ) -> TtlConfig;
+
   fn associations(
     env: Env,
   ) -> Vec<FinalAssociation>;
 
+  //This function will be called by anyone to get up to limit associations starting at offset, the limit is capped to 50.
+  fn associations_page(
+    env: Env,
+    offset: u32,
+    limit: u32,
+  ) -> Vec<FinalAssociation>;
+
+  //This function will be called by anyone to get the number of associations.
+  fn count(
+    env: Env,
+  ) -> u32;
+
   fn total(
     env: Env,
    ) -> i128;
@@ -252,6 +665,7 @@ impl DistributionTrait for DistributionContract{
     associations: Vec<Address>,
     deadline: u64,
   ) -> Result<(), Error>{
+    bump_instance(&env);
     admin.require_auth();
     if env.storage().instance().has(&StorageConst::AdminAddress) {
       log!(
@@ -268,7 +682,7 @@ impl DistributionTrait for DistributionContract{
       );
       return Err(Error::InvalidTimestamp);
     }
-    if associations.len() < 1 {
+    if associations.is_empty() {
       log!(
         &env,
         "Something went wrong, the length of the associations is less than 1."
@@ -278,14 +692,17 @@ impl DistributionTrait for DistributionContract{
 
     let total_amount: i128 = 0;
     let month: u32 = 0;
-    let associations_vec: Vec<FinalAssociation> = init_associations(&env, associations);
     env.storage().instance().set(&StorageConst::AdminAddress, &admin);
+    env.storage().instance().set(&StorageConst::Distributor, &admin);
+    env.storage().instance().set(&StorageConst::Distributed, &false);
     env.storage().instance().set(&StorageConst::AssetAdress, &token_address);
     env.storage().instance().set(&StorageConst::Deadline, &deadline);
-    env.storage().instance().set(&StorageConst::FinalAssociations, &associations_vec);
+    init_associations(&env, &associations);
     env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
     env.storage().instance().set(&StorageConst::DeadlineWithdraw, &deadline);
     env.storage().instance().set(&StorageConst::ClaimMonth, &month);
+    env.storage().instance().set(&StorageConst::RecipientsClaimed, &false);
+    env.events().publish((symbol_short!("init"), EVENTS_VERSION), (admin, token_address, deadline));
     Ok(())
   }
 
@@ -294,15 +711,9 @@ impl DistributionTrait for DistributionContract{
     association: Address,
     admin: Address,
   ) -> Result<(), Error>{
+    bump_instance(&env);
     admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
-      log!(
-        &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
-      );
-      return Err(Error::InvalidAuth);
-    }
+    check_role(&env, &admin, Role::Operator)?;
     let deadline = get_deadline(&env);
     if deadline < get_ledger_timestamp(&env){
       log!(
@@ -311,17 +722,55 @@ impl DistributionTrait for DistributionContract{
       );
       return Err(Error::InvalidTimestamp);
     }
-    let mut associations: Vec<FinalAssociation> = get_associations(&env);
-    add_new_association( &mut associations, &association);
-    env.storage().instance().set(&StorageConst::FinalAssociations, &associations);
+    add_new_association(&env, &association);
+    env.events().publish((symbol_short!("assoc_add"), EVENTS_VERSION), association);
+    Ok(())
+  }
+
+  fn remove_association(
+    env: Env,
+    association: Address,
+    admin: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    let contribution: i128 = remove_association(&env, &association)?;
+    let total_amount: i128 = get_total(&env) - contribution;
+    env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
+    env.events().publish((symbol_short!("assoc_rm"), EVENTS_VERSION), (association, contribution));
+    Ok(())
+  }
+
+  fn suspend_association(
+    env: Env,
+    association: Address,
+    admin: Address,
+    suspended: bool,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    admin.require_auth();
+    check_role(&env, &admin, Role::Operator)?;
+    set_association_suspended(&env, &association, suspended)?;
+    env.events().publish((symbol_short!("suspend"), EVENTS_VERSION), (association, suspended));
     Ok(())
   }
 
   fn deposit(
     env: Env,
+    from: Address,
     association: Address,
     amount: i64,
   ) -> Result<(), Error>{
+    bump_instance(&env);
+    from.require_auth();
+    if get_distributed(&env) {
+      log!(
+        &env,
+        "Something went wrong, the funding has already been distributed."
+      );
+      return Err(Error::AlreadyDistributed);
+    }
     let deadline = get_deadline(&env);
     if deadline < get_ledger_timestamp(&env){
       log!(
@@ -330,29 +779,76 @@ impl DistributionTrait for DistributionContract{
       );
       return Err(Error::InvalidTimestamp);
     }
-    let mut mutable_assoc: Vec<FinalAssociation> = get_associations(&env);
-    let mut total_amount: i128 = get_total(&env);
-    total_amount += amount as i128;
-    //add to storage
-    let new_contributions:Vec<FinalAssociation> = add_contribution(&mut mutable_assoc, &association, amount);
+    if amount <= 0 {
+      log!(
+        &env,
+        "Something went wrong, the amount is not positive."
+      );
+      return Err(Error::InvalidAmount);
+    }
+    add_contribution(&env, &association, amount)?;
+    transfer(&env, &from, &env.current_contract_address(), &(amount as i128));
+    let total_amount: i128 = get_total(&env) + amount as i128;
     env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
-    env.storage().instance().set(&StorageConst::FinalAssociations, &new_contributions);
+    env.events().publish((symbol_short!("deposit"), EVENTS_VERSION), (from, association, amount));
     Ok(())
   }
 
-  fn withdraw(
+  fn distribute(
     env: Env,
-    admin: Address,
+    distributor: Address,
+    associations: Vec<FinalAssociation>,
   ) -> Result<(), Error>{
-    admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
+    bump_instance(&env);
+    distributor.require_auth();
+    if distributor != get_distributor(&env) {
       log!(
         &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
+        "Something went wrong, the address is not the one that initialized the contract."
       );
       return Err(Error::InvalidAuth);
     }
+    if get_distributed(&env) || get_claim_month(&env) > 0 {
+      log!(
+        &env,
+        "Something went wrong, the monthly payouts have already started."
+      );
+      return Err(Error::AlreadyDistributed);
+    }
+    if associations.is_empty() {
+      log!(
+        &env,
+        "Something went wrong, the length of the associations is less than 1."
+      );
+      return Err(Error::InvalidAssociation);
+    }
+    let associations: Vec<FinalAssociation> = add_deposits(&env, &associations);
+    let total_amount: i128 = get_total_contribution(&associations);
+    let balance: i128 = token::Client::new(&env, &get_token_address(&env)).balance(&env.current_contract_address());
+    if balance < total_amount {
+      log!(
+        &env,
+        "Something went wrong, the balance of the contract does not cover the contributions."
+      );
+      return Err(Error::InvalidAmount);
+    }
+    if balance > total_amount {
+      transfer(&env, &env.current_contract_address(), &get_admin_address(&env), &(balance - total_amount));
+    }
+    replace_associations(&env, &associations);
+    env.storage().instance().set(&StorageConst::TotalAmount, &total_amount);
+    env.storage().instance().set(&StorageConst::Distributed, &true);
+    env.events().publish((symbol_short!("distrib"), EVENTS_VERSION), (associations, total_amount));
+    Ok(())
+  }
+
+  fn withdraw(
+    env: Env,
+    admin: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    admin.require_auth();
+    check_role(&env, &admin, Role::Treasurer)?;
     let recipients_claimed: bool = get_recipients_claimed(&env);
     if recipients_claimed{
       log!(
@@ -361,12 +857,12 @@ impl DistributionTrait for DistributionContract{
       );
       return Err(Error::ExpectedExtraValue);
     }
-    let mut mutable_assoc: Vec<FinalAssociation> = get_final_associations(&env);
     let mut claim_month = get_claim_month(&env);
     claim_month += 1;
-    withdraw(&env, &mut mutable_assoc);
+    let paid: i128 = withdraw(&env);
     env.storage().instance().set(&StorageConst::RecipientsClaimed, &true);
     env.storage().instance().set(&StorageConst::ClaimMonth, &claim_month);
+    env.events().publish((symbol_short!("payout"), EVENTS_VERSION), (claim_month, paid));
     Ok(())
   }
 
@@ -374,15 +870,9 @@ impl DistributionTrait for DistributionContract{
     env: Env,
     admin: Address,
   ) -> Result<(), Error>{
+    bump_instance(&env);
     admin.require_auth();
-    let admin_address: Address = get_admin_address(&env);
-    if admin_address != admin {
-      log!(
-        &env,
-        "Something went wrong, the admin address is not the same as the one who called the function."
-      );
-      return Err(Error::InvalidAuth);
-    }
+    check_role(&env, &admin, Role::Operator)?;
     let recipients_claimed: bool = get_recipients_claimed(&env);
     let mut deadline_withdraw: u64 = get_deadline_withdraw(&env);
     
@@ -393,20 +883,220 @@ impl DistributionTrait for DistributionContract{
       );
       return Err(Error::ExpectedExtraValue);
     }
-    let final_assoc = reset_withdraw(&mut get_final_associations(&env));
+    reset_withdraw(&env);
     deadline_withdraw += 2629743;
-    env.storage().instance().set(&StorageConst::FinalAssociations, &final_assoc);
     env.storage().instance().set(&StorageConst::RecipientsClaimed, &false);
     env.storage().instance().set(&StorageConst::DeadlineWithdraw, &deadline_withdraw);
+    env.events().publish((symbol_short!("reset"), EVENTS_VERSION), deadline_withdraw);
+    Ok(())
+  }
+
+  fn set_ttl_config(
+    env: Env,
+    admin: Address,
+    config: TtlConfig,
+  ) -> Result<(), Error>{
+    admin.require_auth();
+    check_role(&env, &admin, Role::Owner)?;
+    if config.low == 0 || config.low > config.high || config.high > MAX_TTL {
+      log!(
+        &env,
+        "Something went wrong, the low threshold is 0 or bigger than the high one, or the high one is bigger than the maximum allowed."
+      );
+      return Err(Error::InvalidTtl);
+    }
+    env.storage().instance().set(&StorageConst::TtlConfig, &config);
+    bump_instance(&env);
+    env.events().publish((symbol_short!("ttl"), EVENTS_VERSION), config);
+    Ok(())
+  }
+
+  fn grant_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    owner.require_auth();
+    check_role(&env, &owner, Role::Owner)?;
+    let mut members: Vec<Address> = get_role_members(&env, role);
+    if members.contains(&address) {
+      log!(
+        &env,
+        "Something went wrong, the address already has the role."
+      );
+      return Err(Error::InvalidRole);
+    }
+    members.push_back(address.clone());
+    env.storage().instance().set(&StorageConst::Roles(role), &members);
+    env.events().publish((symbol_short!("grant"), EVENTS_VERSION), (role, address));
+    Ok(())
+  }
+
+  fn revoke_role(
+    env: Env,
+    owner: Address,
+    role: Role,
+    address: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    owner.require_auth();
+    check_role(&env, &owner, Role::Owner)?;
+    let mut members: Vec<Address> = get_role_members(&env, role);
+    match members.first_index_of(&address) {
+      Some(index) => members.remove(index),
+      None => {
+        log!(
+          &env,
+          "Something went wrong, the address does not have the role."
+        );
+        return Err(Error::InvalidRole);
+      }
+    };
+    env.storage().instance().set(&StorageConst::Roles(role), &members);
+    env.events().publish((symbol_short!("revoke"), EVENTS_VERSION), (role, address));
+    Ok(())
+  }
+
+  fn propose_admin(
+    env: Env,
+    admin: Address,
+    new_admin: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    admin.require_auth();
+    let admin_address: Address = get_admin_address(&env);
+    if admin_address != admin {
+      log!(
+        &env,
+        "Something went wrong, the admin address is not the same as the one who called the function."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    if new_admin == admin {
+      log!(
+        &env,
+        "Something went wrong, the new admin is already the admin."
+      );
+      return Err(Error::InvalidAdmin);
+    }
+    env.storage().instance().set(&StorageConst::PendingAdmin, &new_admin);
+    env.events().publish((symbol_short!("propose"), EVENTS_VERSION), (admin, new_admin));
+    Ok(())
+  }
+
+  fn accept_admin(
+    env: Env,
+    new_admin: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    new_admin.require_auth();
+    if get_pending_admin(&env) != Some(new_admin.clone()) {
+      log!(
+        &env,
+        "Something went wrong, the address is not the proposed admin."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    let old_admin: Address = get_admin_address(&env);
+    env.storage().instance().set(&StorageConst::AdminAddress, &new_admin);
+    env.storage().instance().remove(&StorageConst::PendingAdmin);
+    env.events().publish((symbol_short!("accept"), EVENTS_VERSION), (old_admin, new_admin));
     Ok(())
   }
 
+  fn cancel_admin(
+    env: Env,
+    admin: Address,
+  ) -> Result<(), Error>{
+    bump_instance(&env);
+    admin.require_auth();
+    let admin_address: Address = get_admin_address(&env);
+    if admin_address != admin {
+      log!(
+        &env,
+        "Something went wrong, the admin address is not the same as the one who called the function."
+      );
+      return Err(Error::InvalidAuth);
+    }
+    let new_admin: Address = match get_pending_admin(&env) {
+      Some(new_admin) => new_admin,
+      None => {
+        log!(
+          &env,
+          "Something went wrong, there is no admin proposed."
+        );
+        return Err(Error::InvalidAdmin);
+      }
+    };
+    env.storage().instance().remove(&StorageConst::PendingAdmin);
+    env.events().publish((symbol_short!("unpropose"), EVENTS_VERSION), new_admin);
+    Ok(())
+  }
+
+  fn admin(
+    env: Env,
+  ) -> Address{
+    get_admin_address(&env)
+  }
+
+  fn pending_admin(
+    env: Env,
+  ) -> Option<Address>{
+    get_pending_admin(&env)
+  }
+
+  fn role_members(
+    env: Env,
+    role: Role,
+  ) -> Vec<Address>{
+    get_role_members(&env, role)
+  }
+
+  fn has_role(
+    env: Env,
+    role: Role,
+    address: Address,
+  ) -> bool{
+    has_role(&env, &address, role)
+  }
+
+  fn extend_ttl(
+    env: Env,
+    offset: u32,
+    limit: u32,
+  ){
+    bump_instance(&env);
+    bump_associations(&env, offset, limit);
+  }
+
+  fn ttl_config(
+    env: Env,
+  ) -> TtlConfig{
+    get_ttl_config(&env)
+  }
+
   fn associations(
     env: Env,
   ) -> Vec<FinalAssociation>{
     get_associations(&env)
   }
 
+  fn associations_page(
+    env: Env,
+    offset: u32,
+    limit: u32,
+  ) -> Vec<FinalAssociation>{
+    get_associations_page(&env, offset, limit)
+  }
+
+  fn count(
+    env: Env,
+  ) -> u32{
+    get_association_index(&env).len()
+  }
+
   fn total(
     env: Env,
    ) -> i128{
@@ -418,4 +1108,6 @@ impl DistributionTrait for DistributionContract{
   ) -> u64{
     get_deadline(&env)
   }
-}
\ No newline at end of file
+}
+
+mod test;
\ No newline at end of file
@@ -0,0 +1,150 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+  testutils::{Address as _, Ledger},
+  token, vec, Address, Env,
+};
+
+struct Setup<'a> {
+  env: Env,
+  admin: Address,
+  associations: std::vec::Vec<Address>,
+  token: token::Client<'a>,
+  contract: DistributionContractClient<'a>,
+}
+
+impl Setup<'_> {
+  fn new(count: usize) -> Self {
+    let env: Env = Env::default();
+    env.mock_all_auths();
+    let admin: Address = Address::random(&env);
+    let associations: std::vec::Vec<Address> = (0..count).map(|_| Address::random(&env)).collect();
+    let token_address: Address = env.register_stellar_asset_contract(admin.clone());
+    let contract_id: Address = env.register_contract(None, DistributionContract);
+    let contract = DistributionContractClient::new(&env, &contract_id);
+    contract.init(&admin, &token_address, &Vec::from_slice(&env, &associations), &0);
+    Setup {
+      token: token::Client::new(&env, &token_address),
+      contract,
+      admin,
+      associations,
+      env,
+    }
+  }
+
+  fn final_associations(&self, contributions: &[i128]) -> Vec<FinalAssociation> {
+    let mut final_associations: Vec<FinalAssociation> = Vec::new(&self.env);
+    for (name, contribution) in self.associations.iter().zip(contributions) {
+      final_associations.push_back(FinalAssociation {
+        name: name.clone(),
+        contribution: *contribution,
+        direct: *contribution,
+        matched: 0,
+        capped: false,
+        suspended: false,
+        has_withdrawn: false,
+      });
+    }
+    final_associations
+  }
+
+  //Function to hand the contract its funds and the final associations, as the withdraw of the voting contract does.
+  fn distribute(&self, contributions: &[i128]) {
+    let total: i128 = contributions.iter().sum();
+    token::StellarAssetClient::new(&self.env, &self.token.address).mint(&self.contract.address, &total);
+    self.contract.distribute(&self.admin, &self.final_associations(contributions));
+  }
+
+  //Function to pay a month and open the next one once the month is over.
+  fn pay_month(&self) {
+    self.contract.withdraw(&self.admin);
+    self.env.ledger().with_mut(|ledger| ledger.timestamp += 2629743);
+    self.contract.reset_deadline(&self.admin);
+  }
+}
+
+#[test]
+fn test_removed_association_returns_what_was_not_paid() {
+  let setup = Setup::new(2);
+  let (first, second) = (setup.associations[0].clone(), setup.associations[1].clone());
+  setup.distribute(&[120, 240]);
+  setup.pay_month();
+  // The first association is skipped by the second month.
+  setup.contract.suspend_association(&first, &setup.admin, &true);
+  setup.pay_month();
+  assert_eq!(setup.token.balance(&first), 10);
+  assert_eq!(setup.token.balance(&second), 40);
+
+  setup.contract.remove_association(&first, &setup.admin);
+  assert_eq!(setup.token.balance(&setup.admin), 110);
+  assert_eq!(setup.contract.total(), 240);
+  assert_eq!(setup.contract.associations(), vec![&setup.env, FinalAssociation {
+    name: second,
+    contribution: 240,
+    direct: 240,
+    matched: 0,
+    capped: false,
+    suspended: false,
+    has_withdrawn: false,
+  }]);
+}
+
+#[test]
+fn test_distribute_is_called_once_by_the_initializer_with_the_funds() {
+  let setup = Setup::new(2);
+  let final_associations: Vec<FinalAssociation> = setup.final_associations(&[120, 240]);
+  token::StellarAssetClient::new(&setup.env, &setup.token.address).mint(&setup.contract.address, &300);
+  assert_eq!(setup.contract.try_distribute(&setup.admin, &final_associations), Err(Ok(Error::InvalidAmount)));
+
+  token::StellarAssetClient::new(&setup.env, &setup.token.address).mint(&setup.contract.address, &60);
+  let treasurer: Address = Address::random(&setup.env);
+  setup.contract.grant_role(&setup.admin, &Role::Treasurer, &treasurer);
+  assert_eq!(setup.contract.try_distribute(&treasurer, &final_associations), Err(Ok(Error::InvalidAuth)));
+  setup.contract.distribute(&setup.admin, &final_associations);
+  assert_eq!(setup.contract.total(), 360);
+  assert_eq!(setup.contract.try_distribute(&setup.admin, &final_associations), Err(Ok(Error::AlreadyDistributed)));
+}
+
+#[test]
+fn test_deposits_are_funded_and_closed_by_the_distribution() {
+  let setup = Setup::new(2);
+  let donor: Address = Address::random(&setup.env);
+  let asset = token::StellarAssetClient::new(&setup.env, &setup.token.address);
+  asset.mint(&donor, &100);
+  setup.contract.deposit(&donor, &setup.associations[0], &12);
+  assert_eq!(setup.token.balance(&donor), 88);
+  let unknown: Address = Address::random(&setup.env);
+  assert_eq!(setup.contract.try_deposit(&donor, &unknown, &12), Err(Ok(Error::InvalidAssociation)));
+  assert_eq!(setup.contract.try_deposit(&donor, &setup.associations[0], &0), Err(Ok(Error::InvalidAmount)));
+
+  // Tokens sent straight to the contract do not block the distribution, they go back to the admin.
+  asset.mint(&setup.contract.address, &1);
+  setup.distribute(&[120, 240]);
+  assert_eq!(setup.token.balance(&setup.admin), 1);
+  assert_eq!(setup.token.balance(&setup.contract.address), 372);
+  assert_eq!(setup.contract.total(), 372);
+  assert_eq!(*setup.contract.associations().get(0).unwrap().get_contribution(), 132);
+
+  assert_eq!(setup.contract.try_deposit(&donor, &setup.associations[0], &12), Err(Ok(Error::AlreadyDistributed)));
+  assert_eq!(setup.token.balance(&donor), 88);
+}
+
+#[test]
+fn test_roles_are_granted_and_revoked_by_the_owners() {
+  let setup = Setup::new(2);
+  let (owner, operator) = (Address::random(&setup.env), Address::random(&setup.env));
+  assert_eq!(setup.contract.try_grant_role(&operator, &Role::Operator, &operator), Err(Ok(Error::InvalidAuth)));
+  setup.contract.grant_role(&setup.admin, &Role::Owner, &owner);
+  setup.contract.grant_role(&owner, &Role::Operator, &operator);
+  assert_eq!(setup.contract.try_grant_role(&owner, &Role::Operator, &operator), Err(Ok(Error::InvalidRole)));
+  assert_eq!(setup.contract.role_members(&Role::Operator), vec![&setup.env, operator.clone()]);
+  assert!(setup.contract.has_role(&Role::Treasurer, &owner));
+  assert!(!setup.contract.has_role(&Role::Treasurer, &operator));
+
+  setup.contract.suspend_association(&setup.associations[0], &operator, &true);
+  assert_eq!(setup.contract.try_withdraw(&operator), Err(Ok(Error::InvalidAuth)));
+  setup.contract.revoke_role(&owner, &Role::Operator, &operator);
+  assert_eq!(setup.contract.try_suspend_association(&setup.associations[0], &operator, &false), Err(Ok(Error::InvalidAuth)));
+  assert_eq!(setup.contract.try_revoke_role(&owner, &Role::Owner, &setup.admin), Err(Ok(Error::InvalidRole)));
+}